@@ -0,0 +1,88 @@
+//! Compares the `memcpy`-based bulk operations with pushing and iterating item by item.
+//!
+//! Run with `cargo bench --bench copy`.
+
+extern crate circular_queue;
+
+use circular_queue::CircularQueue;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const CAPACITY: usize = 4096;
+const CHUNK: usize = 1500;
+const ITERATIONS: u32 = 20_000;
+
+fn bench<F: FnMut()>(name: &str, mut f: F) -> Duration {
+    // Warm up the caches and the branch predictor.
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let per_iter = start.elapsed() / ITERATIONS;
+
+    println!("{:<40} {:>10.2?}", name, per_iter);
+    per_iter
+}
+
+fn main() {
+    let src: Vec<u8> = (0..CHUNK).map(|x| x as u8).collect();
+    let mut dst = vec![0u8; CAPACITY];
+
+    let mut queue = CircularQueue::with_capacity(CAPACITY);
+    // Start from a wrapped-around state so that every operation crosses the boundary.
+    queue.write_from_slice(&src);
+    queue.write_from_slice(&src);
+    queue.write_from_slice(&src);
+
+    println!("u8, capacity {}, chunks of {} bytes", CAPACITY, CHUNK);
+
+    let slow = bench("push() in a loop", || {
+        for &x in black_box(&src[..]) {
+            black_box(queue.push(x));
+        }
+    });
+    let fast = bench("write_from_slice()", || {
+        black_box(queue.write_from_slice(black_box(&src[..])));
+    });
+    println!(
+        "{:<40} {:>9.1}x",
+        "speedup",
+        slow.as_secs_f64() / fast.as_secs_f64()
+    );
+
+    let slow = bench("asc_iter() into a slice", || {
+        for (d, s) in dst.iter_mut().zip(queue.asc_iter()) {
+            *d = *s;
+        }
+        black_box(&dst);
+    });
+    let fast = bench("copy_to_slice_asc()", || {
+        queue.copy_to_slice_asc(&mut dst);
+        black_box(&dst);
+    });
+    println!(
+        "{:<40} {:>9.1}x",
+        "speedup",
+        slow.as_secs_f64() / fast.as_secs_f64()
+    );
+
+    let slow = bench("iter().take(n) into a slice", || {
+        for (d, s) in dst[..CHUNK].iter_mut().rev().zip(queue.iter()) {
+            *d = *s;
+        }
+        black_box(&dst);
+    });
+    let fast = bench("copy_newest_n()", || {
+        queue.copy_newest_n(&mut dst[..CHUNK]);
+        black_box(&dst);
+    });
+    println!(
+        "{:<40} {:>9.1}x",
+        "speedup",
+        slow.as_secs_f64() / fast.as_secs_f64()
+    );
+}
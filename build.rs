@@ -1,13 +1,48 @@
 extern crate version_check;
 
+use std::env;
+
 fn main() {
     println!("cargo:rustc-check-cfg=cfg(has_extern_crate_alloc)");
     if version_check::is_min_version("1.36.0").unwrap_or(false) {
         println!("cargo:rustc-cfg=has_extern_crate_alloc");
     }
 
+    // Before `extern crate alloc` the crate always depends on std.
+    println!("cargo:rustc-check-cfg=cfg(has_alloc)");
+    if env::var_os("CARGO_FEATURE_ALLOC").is_some()
+        || !version_check::is_min_version("1.36.0").unwrap_or(false)
+    {
+        println!("cargo:rustc-cfg=has_alloc");
+    }
+
+    println!("cargo:rustc-check-cfg=cfg(has_sort_unstable)");
+    if version_check::is_min_version("1.20.0").unwrap_or(false) {
+        println!("cargo:rustc-cfg=has_sort_unstable");
+    }
+
     println!("cargo:rustc-check-cfg=cfg(has_relaxed_orphan_rule)");
     if version_check::is_min_version("1.41.0").unwrap_or(false) {
         println!("cargo:rustc-cfg=has_relaxed_orphan_rule");
     }
+
+    println!("cargo:rustc-check-cfg=cfg(has_global_allocator)");
+    if version_check::is_min_version("1.28.0").unwrap_or(false) {
+        println!("cargo:rustc-cfg=has_global_allocator");
+    }
+
+    println!("cargo:rustc-check-cfg=cfg(has_range_bounds)");
+    if version_check::is_min_version("1.28.0").unwrap_or(false) {
+        println!("cargo:rustc-cfg=has_range_bounds");
+    }
+
+    println!("cargo:rustc-check-cfg=cfg(has_const_generics)");
+    if version_check::is_min_version("1.51.0").unwrap_or(false) {
+        println!("cargo:rustc-cfg=has_const_generics");
+    }
+
+    println!("cargo:rustc-check-cfg=cfg(has_try_reserve)");
+    if version_check::is_min_version("1.57.0").unwrap_or(false) {
+        println!("cargo:rustc-cfg=has_try_reserve");
+    }
 }
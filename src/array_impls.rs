@@ -0,0 +1,187 @@
+//! Implementations involving arrays of any size.
+
+use super::*;
+
+impl<T, S, U, const N: usize> PartialEq<[U; N]> for CircularQueue<T, S>
+where
+    T: PartialEq<U>,
+    S: Storage<T>,
+{
+    #[inline]
+    fn eq(&self, other: &[U; N]) -> bool {
+        self.eq_asc_slice(other)
+    }
+}
+
+impl<'a, T, S, U, const N: usize> PartialEq<&'a [U; N]> for CircularQueue<T, S>
+where
+    T: PartialEq<U>,
+    S: Storage<T>,
+{
+    #[inline]
+    fn eq(&self, other: &&'a [U; N]) -> bool {
+        self.eq_asc_slice(*other)
+    }
+}
+
+#[cfg(has_alloc)]
+impl<T, const N: usize> From<[T; N]> for CircularQueue<T> {
+    /// Converts an array going from the oldest items to the newest ones into a full queue with
+    /// capacity `N`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::from([1, 2, 3]);
+    /// assert!(queue.is_full());
+    ///
+    /// assert_eq!(queue.push(4), Some(1));
+    /// ```
+    #[inline]
+    fn from(array: [T; N]) -> Self {
+        Vec::from(array).into()
+    }
+}
+
+/// An iterator over overlapping windows of `N` consecutive elements of the queue's contents,
+/// created with `CircularQueue::array_windows()`.
+pub struct ArrayWindows<'a, T: 'a, const N: usize> {
+    windows: Windows<'a, T>,
+}
+
+impl<'a, T, const N: usize> ArrayWindows<'a, T, N> {
+    #[inline]
+    fn array(window: Window<'a, T>) -> [&'a T; N] {
+        let mut array = [window.get(0).unwrap(); N];
+        for (i, x) in array.iter_mut().enumerate().skip(1) {
+            *x = window.get(i).unwrap();
+        }
+        array
+    }
+}
+
+impl<'a, T, const N: usize> Iterator for ArrayWindows<'a, T, N> {
+    type Item = [&'a T; N];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.windows.next().map(Self::array)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.windows.size_hint()
+    }
+}
+
+impl<'a, T, const N: usize> DoubleEndedIterator for ArrayWindows<'a, T, N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.windows.next_back().map(Self::array)
+    }
+}
+
+impl<'a, T, const N: usize> ExactSizeIterator for ArrayWindows<'a, T, N> {}
+
+impl<'a, T, const N: usize> Clone for ArrayWindows<'a, T, N> {
+    #[inline]
+    fn clone(&self) -> Self {
+        ArrayWindows {
+            windows: self.windows.clone(),
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug, const N: usize> fmt::Debug for ArrayWindows<'a, T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<T, S: Storage<T>> CircularQueue<T, S> {
+    /// Returns an iterator over all overlapping windows of `N` consecutive elements as arrays of
+    /// references, going from the oldest items to the newest ones.
+    ///
+    /// This is `windows(N)` with the window size known at compile time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut samples = CircularQueue::with_capacity(4);
+    /// for &x in &[1, 5, 2, 8, 3] {
+    ///     samples.push(x);
+    /// }
+    ///
+    /// let peaks: Vec<i32> = samples
+    ///     .array_windows()
+    ///     .filter(|[a, b, c]| b > a && b > c)
+    ///     .map(|[_, b, _]| *b)
+    ///     .collect();
+    /// assert_eq!(peaks, [8]);
+    /// ```
+    #[inline]
+    pub fn array_windows<const N: usize>(&self) -> ArrayWindows<'_, T, N> {
+        ArrayWindows {
+            windows: self.windows(N),
+        }
+    }
+}
+
+#[cfg(all(test, has_alloc))]
+mod tests {
+    use super::*;
+    #[cfg(has_extern_crate_alloc)]
+    use alloc::vec;
+
+    #[test]
+    fn from_array() {
+        let mut q = CircularQueue::from([1, 2, 3]);
+        assert_eq!(q.capacity(), 3);
+        assert_eq!(q, [1, 2, 3]);
+
+        q.push(4);
+        assert_eq!(q, [2, 3, 4]);
+    }
+
+    #[test]
+    fn array_eq() {
+        let mut q = CircularQueue::with_capacity(3);
+        let empty: [i32; 0] = [];
+        assert_eq!(q, empty);
+
+        q.push(1);
+        q.push(2);
+        assert_eq!(q, [1, 2]);
+        assert_eq!(q, &[1, 2]);
+        assert_ne!(q, [2, 1]);
+
+        q.push(3);
+        q.push(4);
+        assert_eq!(q, [2, 3, 4]);
+        assert_ne!(q, [2, 3]);
+        assert_ne!(q, [2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn array_windows() {
+        let mut q = CircularQueue::with_capacity(4);
+        q.extend(vec![1, 2, 3, 4, 5, 6]);
+
+        let windows: Vec<[i32; 3]> = q.array_windows().map(|[a, b, c]| [*a, *b, *c]).collect();
+        assert_eq!(windows, [[3, 4, 5], [4, 5, 6]]);
+
+        let last = q.array_windows::<4>().next_back().unwrap();
+        assert_eq!(last, [&3, &4, &5, &6]);
+
+        assert_eq!(q.array_windows::<5>().len(), 0);
+        assert_eq!(q.array_windows::<1>().count(), 4);
+    }
+}
@@ -0,0 +1,160 @@
+//! A builder for `CircularQueue`.
+
+use super::*;
+
+/// A builder for `CircularQueue<T>`, created with `CircularQueue::builder()`.
+///
+/// # Examples
+///
+/// ```
+/// use circular_queue::{CircularQueue, Preallocation};
+///
+/// let queue = CircularQueue::builder()
+///     .capacity(1024)
+///     .preallocation(Preallocation::Lazy)
+///     .contents(vec![1, 2, 3])
+///     .build();
+///
+/// assert_eq!(queue.capacity(), 1024);
+/// assert_eq!(queue, vec![1, 2, 3]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CircularQueueBuilder<T> {
+    capacity: usize,
+    overflow_policy: OverflowPolicy,
+    preallocation: Preallocation,
+    contents: Vec<T>,
+}
+
+impl<T> CircularQueueBuilder<T> {
+    /// Creates a builder for a queue with zero capacity and default settings.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            capacity: 0,
+            overflow_policy: OverflowPolicy::default(),
+            preallocation: Preallocation::default(),
+            contents: Vec::new(),
+        }
+    }
+
+    /// Sets the capacity of the queue.
+    #[inline]
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets what `push()` does when the queue is full.
+    #[inline]
+    pub fn overflow_policy(mut self, overflow_policy: OverflowPolicy) -> Self {
+        self.overflow_policy = overflow_policy;
+        self
+    }
+
+    /// Sets when the queue allocates memory for its elements.
+    #[inline]
+    pub fn preallocation(mut self, preallocation: Preallocation) -> Self {
+        self.preallocation = preallocation;
+        self
+    }
+
+    /// Sets the initial contents of the queue, going from the oldest items to the newest ones.
+    ///
+    /// The items are pushed into the queue in order according to the overflow policy, so if there
+    /// are more of them than the capacity, only the last ones are kept with
+    /// `OverflowPolicy::Overwrite` and only the first ones with `OverflowPolicy::Reject`. Either
+    /// way, the items discarded this way don't count as evicted, and the counters of the built
+    /// queue start at zero.
+    #[inline]
+    pub fn contents<I: IntoIterator<Item = T>>(mut self, contents: I) -> Self {
+        self.contents = contents.into_iter().collect();
+        self
+    }
+
+    /// Builds the queue.
+    #[inline]
+    pub fn build(self) -> CircularQueue<T> {
+        let data = match self.preallocation {
+            Preallocation::Eager => Vec::with_capacity(self.capacity),
+            Preallocation::Lazy => Vec::new(),
+        };
+
+        let mut queue = CircularQueue {
+            data,
+            capacity: self.capacity,
+            insertion_index: 0,
+            overflow_policy: self.overflow_policy,
+            evictions: Evictions::new(),
+        };
+        queue.extend(self.contents);
+        // There is no eviction callback yet, so only the counters have to be reset.
+        queue.evictions = Evictions::new();
+        queue
+    }
+}
+
+impl<T> Default for CircularQueueBuilder<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(has_extern_crate_alloc)]
+    use alloc::vec;
+
+    #[test]
+    fn defaults() {
+        let q: CircularQueue<i32> = CircularQueue::builder().build();
+        assert_eq!(q.capacity(), 0);
+        assert_eq!(q.overflow_policy(), OverflowPolicy::Overwrite);
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn lazy_preallocation() {
+        let mut q = CircularQueue::builder()
+            .capacity(10)
+            .preallocation(Preallocation::Lazy)
+            .build();
+        assert_eq!(q.data.capacity(), 0);
+
+        for i in 0..15 {
+            q.push(i);
+            assert!(q.data.capacity() <= 10);
+        }
+
+        assert_eq!(q, vec![5, 6, 7, 8, 9, 10, 11, 12, 13, 14]);
+    }
+
+    #[test]
+    fn eager_preallocation() {
+        let q: CircularQueue<i32> = CircularQueue::builder().capacity(10).build();
+        assert!(q.data.capacity() >= 10);
+    }
+
+    #[test]
+    fn contents_overwrite() {
+        let q = CircularQueue::builder()
+            .capacity(3)
+            .contents(vec![1, 2, 3, 4, 5])
+            .build();
+        assert_eq!(q, vec![3, 4, 5]);
+        assert_eq!(q.total_pushed(), 0);
+        assert_eq!(q.total_evicted(), 0);
+    }
+
+    #[test]
+    fn contents_reject() {
+        let q = CircularQueue::builder()
+            .capacity(3)
+            .overflow_policy(OverflowPolicy::Reject)
+            .contents(vec![1, 2, 3, 4, 5])
+            .build();
+        assert_eq!(q, vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,326 @@
+//! A byte stream on top of `CircularQueue<u8>`.
+
+use super::*;
+
+#[cfg(all(has_extern_crate_alloc, feature = "std"))]
+use std::io;
+#[cfg(not(has_extern_crate_alloc))]
+use std::io;
+
+/// A bounded byte stream: bytes are written at the newest end and read from the oldest end.
+///
+/// With `OverflowPolicy::Overwrite`, writing into a full queue discards the oldest unread bytes.
+/// With `OverflowPolicy::Reject`, writes are cut short to the free space instead.
+///
+/// With the `std` feature, `ByteQueue` implements `io::Write`, `io::Read` and `io::BufRead`. An
+/// empty queue reads as the end of the stream, and writing into a full queue with
+/// `OverflowPolicy::Reject` writes zero bytes.
+///
+/// # Examples
+///
+/// ```
+/// use circular_queue::ByteQueue;
+///
+/// let mut queue = ByteQueue::with_capacity(8);
+/// assert_eq!(queue.write(b"hello, "), 7);
+/// assert_eq!(queue.write(b"world"), 5);
+///
+/// let mut buf = [0; 8];
+/// assert_eq!(queue.read(&mut buf), 8);
+/// assert_eq!(&buf, b"o, world");
+/// assert_eq!(queue.len(), 0);
+/// ```
+#[cfg(has_alloc)]
+#[derive(Clone)]
+pub struct ByteQueue<S = Vec<u8>> {
+    // Always overwrites: the slots of the bytes that were read are free.
+    queue: CircularQueue<u8, S>,
+    // The number of the newest bytes in `queue` that weren't read yet.
+    unread: usize,
+    overflow_policy: OverflowPolicy,
+}
+
+/// A bounded byte stream: bytes are written at the newest end and read from the oldest end.
+///
+/// With `OverflowPolicy::Overwrite`, writing into a full queue discards the oldest unread bytes.
+/// With `OverflowPolicy::Reject`, writes are cut short to the free space instead.
+#[cfg(not(has_alloc))]
+#[derive(Clone)]
+pub struct ByteQueue<S> {
+    queue: CircularQueue<u8, S>,
+    unread: usize,
+    overflow_policy: OverflowPolicy,
+}
+
+#[cfg(has_alloc)]
+impl ByteQueue {
+    /// Constructs a new, empty `ByteQueue` with the requested capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::from_storage(Vec::with_capacity(capacity))
+    }
+}
+
+impl<S: Storage<u8>> ByteQueue<S> {
+    /// Constructs a new, empty `ByteQueue` on top of the provided storage.
+    ///
+    /// The capacity is the capacity of the storage, and the bytes already in the storage are
+    /// discarded.
+    #[inline]
+    pub fn from_storage(data: S) -> Self {
+        let mut queue = CircularQueue::from_storage(data);
+        queue.clear();
+        Self {
+            queue,
+            unread: 0,
+            overflow_policy: OverflowPolicy::Overwrite,
+        }
+    }
+
+    /// Returns the number of unread bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.unread
+    }
+
+    /// Returns `true` if there are no unread bytes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the unread bytes fill the whole capacity.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Returns the capacity of the queue.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+
+    /// Returns what writing into a full queue does.
+    #[inline]
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.overflow_policy
+    }
+
+    /// Sets what writing into a full queue does.
+    #[inline]
+    pub fn set_overflow_policy(&mut self, overflow_policy: OverflowPolicy) {
+        self.overflow_policy = overflow_policy;
+    }
+
+    /// Discards all unread bytes.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.queue.clear();
+        self.unread = 0;
+    }
+
+    /// Returns the unread bytes, going from the oldest ones to the newest ones, as two slices.
+    ///
+    /// The first slice is empty only if the queue is empty.
+    #[inline]
+    pub fn as_slices(&self) -> (&[u8], &[u8]) {
+        let data = self.queue.data.as_slice();
+        let (newer, older) = data.split_at(self.queue.insertion_index);
+
+        if self.unread <= newer.len() {
+            (&newer[newer.len() - self.unread..], &[])
+        } else {
+            (&older[data.len() - self.unread..], newer)
+        }
+    }
+
+    /// Writes the bytes of `buf` and returns the number of bytes written.
+    ///
+    /// With `OverflowPolicy::Reject`, only as many bytes as there is free space for are written.
+    #[inline]
+    pub fn write(&mut self, buf: &[u8]) -> usize {
+        let buf = match self.overflow_policy {
+            OverflowPolicy::Overwrite => buf,
+            OverflowPolicy::Reject => &buf[..cmp::min(buf.len(), self.capacity() - self.unread)],
+        };
+
+        self.queue.write_from_slice(buf);
+        self.unread = cmp::min(self.unread + buf.len(), self.capacity());
+        buf.len()
+    }
+
+    /// Reads the oldest unread bytes into `buf` and returns the number of bytes read.
+    #[inline]
+    pub fn read(&mut self, buf: &mut [u8]) -> usize {
+        let (a, b) = self.as_slices();
+        let from_a = cmp::min(buf.len(), a.len());
+        let from_b = cmp::min(buf.len() - from_a, b.len());
+        buf[..from_a].copy_from_slice(&a[..from_a]);
+        buf[from_a..from_a + from_b].copy_from_slice(&b[..from_b]);
+
+        self.consume(from_a + from_b);
+        from_a + from_b
+    }
+
+    /// Marks `amount` of the oldest unread bytes as read.
+    ///
+    /// Consuming more bytes than there are unread empties the queue.
+    #[inline]
+    pub fn consume(&mut self, amount: usize) {
+        self.unread -= cmp::min(amount, self.unread);
+    }
+}
+
+impl<S: Storage<u8>> fmt::Debug for ByteQueue<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (a, b) = self.as_slices();
+        f.debug_struct("ByteQueue")
+            .field("unread", &(a, b))
+            .field("capacity", &self.capacity())
+            .field("overflow_policy", &self.overflow_policy)
+            .finish()
+    }
+}
+
+#[cfg(any(feature = "std", not(has_extern_crate_alloc)))]
+impl<S: Storage<u8>> io::Write for ByteQueue<S> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(ByteQueue::write(self, buf))
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(any(feature = "std", not(has_extern_crate_alloc)))]
+impl<S: Storage<u8>> io::Read for ByteQueue<S> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        Ok(ByteQueue::read(self, buf))
+    }
+}
+
+#[cfg(any(feature = "std", not(has_extern_crate_alloc)))]
+impl<S: Storage<u8>> io::BufRead for ByteQueue<S> {
+    /// Returns the oldest contiguous run of unread bytes without copying them.
+    #[inline]
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.as_slices().0)
+    }
+
+    #[inline]
+    fn consume(&mut self, amount: usize) {
+        ByteQueue::consume(self, amount)
+    }
+}
+
+#[cfg(all(test, has_alloc))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overwrite() {
+        let mut q = ByteQueue::with_capacity(4);
+        assert!(q.is_empty());
+
+        assert_eq!(q.write(b"abc"), 3);
+        assert_eq!(q.as_slices(), (&b"abc"[..], &b""[..]));
+
+        assert_eq!(q.write(b"de"), 2);
+        assert!(q.is_full());
+        assert_eq!(q.as_slices(), (&b"bcd"[..], &b"e"[..]));
+
+        assert_eq!(q.write(b"fghijk"), 6);
+        assert_eq!(q.len(), 4);
+        let (a, b) = q.as_slices();
+        assert_eq!([a, b].concat(), b"hijk");
+    }
+
+    #[test]
+    fn reject() {
+        let mut q = ByteQueue::with_capacity(4);
+        q.set_overflow_policy(OverflowPolicy::Reject);
+
+        assert_eq!(q.write(b"abc"), 3);
+        assert_eq!(q.write(b"def"), 1);
+        assert_eq!(q.write(b"g"), 0);
+
+        let mut buf = [0; 2];
+        assert_eq!(q.read(&mut buf), 2);
+        assert_eq!(&buf, b"ab");
+
+        // The slots of the bytes that were read are free again.
+        assert_eq!(q.write(b"ghi"), 2);
+        let (a, b) = q.as_slices();
+        assert_eq!([a, b].concat(), b"cdgh");
+    }
+
+    #[test]
+    fn read_across_wrap() {
+        let mut q = ByteQueue::with_capacity(5);
+        let mut buf = [0; 3];
+
+        for round in 0..20u8 {
+            let chunk = [round, round + 1, round + 2];
+            assert_eq!(q.write(&chunk), 3);
+            assert_eq!(q.read(&mut buf), 3);
+            assert_eq!(buf, chunk);
+            assert!(q.is_empty());
+        }
+
+        assert_eq!(q.read(&mut buf), 0);
+    }
+
+    #[test]
+    fn consume_too_much() {
+        let mut q = ByteQueue::with_capacity(4);
+        q.write(b"abc");
+        q.consume(10);
+        assert!(q.is_empty());
+        assert_eq!(q.as_slices(), (&b""[..], &b""[..]));
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut q = ByteQueue::with_capacity(0);
+        assert_eq!(q.write(b"abc"), 3);
+        assert!(q.is_empty());
+
+        q.set_overflow_policy(OverflowPolicy::Reject);
+        assert_eq!(q.write(b"abc"), 0);
+    }
+
+    #[cfg(any(feature = "std", not(has_extern_crate_alloc)))]
+    #[test]
+    fn io() {
+        use std::io::{BufRead, ErrorKind, Read, Write};
+        use std::string::String;
+
+        let mut q = ByteQueue::with_capacity(8);
+        q.write_all(b"one\n").unwrap();
+        q.write_all(b"two\n").unwrap();
+        q.write_all(b"three\n").unwrap();
+        assert_eq!(q.len(), 8);
+
+        // The oldest contiguous part is exposed without copying.
+        assert_eq!(q.fill_buf().unwrap(), b"o\n");
+        BufRead::consume(&mut q, 2);
+
+        let mut line = String::new();
+        q.read_line(&mut line).unwrap();
+        assert_eq!(line, "three\n");
+
+        q.write_all(b"four").unwrap();
+        let mut rest = Vec::new();
+        q.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, b"four");
+
+        q.set_overflow_policy(OverflowPolicy::Reject);
+        q.write_all(b"12345678").unwrap();
+        assert_eq!(q.write_all(b"9").unwrap_err().kind(), ErrorKind::WriteZero);
+    }
+}
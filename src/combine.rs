@@ -0,0 +1,342 @@
+//! Moving elements between queues.
+
+use super::*;
+
+impl<T, S: Storage<T>> CircularQueue<T, S> {
+    /// Moves all elements of `other` into the queue, going from the oldest ones to the newest ones,
+    /// and returns the number of elements evicted to make room for them.
+    ///
+    /// The elements are pushed one by one, so with `OverflowPolicy::Overwrite` the oldest elements
+    /// are evicted and reported to the eviction callback like in `push()`. This includes the
+    /// oldest elements of `other` if it holds more elements than the capacity. With
+    /// `OverflowPolicy::Reject`, only as many of the oldest elements of `other` as there is free
+    /// space for are moved, and the rest stay in `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(4);
+    /// queue.extend(vec![1, 2, 3]);
+    ///
+    /// let mut other = CircularQueue::with_capacity(2);
+    /// other.extend(vec![4, 5]);
+    ///
+    /// assert_eq!(queue.append(&mut other), 1);
+    /// assert_eq!(queue, [2, 3, 4, 5]);
+    /// assert!(other.is_empty());
+    /// ```
+    pub fn append<S2: Storage<T>>(&mut self, other: &mut CircularQueue<T, S2>) -> usize {
+        let evicted = self.total_evicted();
+
+        let count = match self.overflow_policy {
+            OverflowPolicy::Overwrite => other.len(),
+            OverflowPolicy::Reject => cmp::min(other.len(), self.capacity() - self.len()),
+        };
+
+        // Put the oldest elements of `other` at the end of its storage, where they can be popped.
+        other.make_contiguous().reverse();
+        for _ in 0..count {
+            let x = other.data.pop().unwrap();
+            self.push(x);
+        }
+        other.data.as_mut_slice().reverse();
+        if count > 0 {
+            other.insertion_index = other.len();
+        }
+
+        (self.total_evicted() - evicted) as usize
+    }
+}
+
+impl<T> CircularQueue<T> {
+    /// Splits the queue in two at `at`, counting from the oldest element.
+    ///
+    /// Returns a new queue with the elements from `at` up to the newest one, leaving the oldest
+    /// `at` elements in the queue. The new queue has the same capacity and overflow policy, and
+    /// its counters start from zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is greater than the length of the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(4);
+    /// queue.extend(vec![1, 2, 3, 4, 5]);
+    ///
+    /// let newer = queue.split_off(1);
+    /// assert_eq!(queue, [2]);
+    /// assert_eq!(newer, [3, 4, 5]);
+    /// assert_eq!(newer.capacity(), 4);
+    /// ```
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len(), "`at` is out of bounds");
+
+        self.make_contiguous();
+        let newer = self.data.split_off(at);
+        if at < self.capacity() {
+            self.insertion_index = at;
+        }
+
+        let insertion_index = if newer.len() == self.capacity() {
+            0
+        } else {
+            newer.len()
+        };
+
+        CircularQueue {
+            data: newer,
+            capacity: self.capacity,
+            insertion_index,
+            overflow_policy: self.overflow_policy,
+            evictions: Evictions::new(),
+        }
+    }
+
+    /// Merges two queues of items ordered by `key` into a new queue with the given capacity.
+    ///
+    /// Both queues must go from the oldest items to the newest ones in ascending order of `key`,
+    /// such as timestamps. The new queue keeps the `capacity` items with the greatest keys overall
+    /// in the same order. Items with equal keys from `self` go before those from `other`.
+    ///
+    /// The new queue takes over the overflow policy, the counters and the eviction callback of
+    /// `self`. The items of `other` count as pushed into it, and the items that don't fit count as
+    /// evicted from it and are passed to the callback in the same order. The counters and the
+    /// callback of `other` are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut worker_a = CircularQueue::with_capacity(3);
+    /// worker_a.extend(vec![(1, "a1"), (4, "a4"), (6, "a6")]);
+    ///
+    /// let mut worker_b = CircularQueue::with_capacity(3);
+    /// worker_b.extend(vec![(2, "b2"), (3, "b3"), (5, "b5")]);
+    ///
+    /// let merged = worker_a.merge_by_key(worker_b, 4, |&(time, _)| time);
+    /// assert_eq!(merged, [(3, "b3"), (4, "a4"), (5, "b5"), (6, "a6")]);
+    /// ```
+    pub fn merge_by_key<K, F>(mut self, other: Self, capacity: usize, mut key: F) -> Self
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        let overflow_policy = self.overflow_policy;
+        let mut evictions = replace(&mut self.evictions, Evictions::new());
+        evictions.pushed(other.len());
+
+        let mut a = self.into_vec_asc();
+        let mut b = other.into_vec_asc();
+
+        // Take the items with the greatest keys from the newest ends.
+        let mut newest = Vec::with_capacity(cmp::min(capacity, a.len() + b.len()));
+        while newest.len() < capacity {
+            let from_a = match (a.last(), b.last()) {
+                (Some(x), Some(y)) => key(x) > key(y),
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+
+            let x = if from_a { a.pop() } else { b.pop() };
+            newest.extend(x);
+        }
+
+        // The items left over didn't fit.
+        if evictions.has_callback() {
+            let (mut i, mut j) = (0, 0);
+            while i < a.len() || j < b.len() {
+                let from_a = j == b.len() || (i < a.len() && key(&a[i]) <= key(&b[j]));
+                if from_a {
+                    evictions.evict(&a[i]);
+                    i += 1;
+                } else {
+                    evictions.evict(&b[j]);
+                    j += 1;
+                }
+            }
+        } else {
+            evictions.evicted(a.len() + b.len());
+        }
+
+        newest.reverse();
+        let insertion_index = if newest.len() == capacity {
+            0
+        } else {
+            newest.len()
+        };
+
+        CircularQueue {
+            data: newest,
+            capacity,
+            insertion_index,
+            overflow_policy,
+            evictions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(has_extern_crate_alloc)]
+    use alloc::vec;
+    use tests::wrapped_queues;
+
+    #[test]
+    fn append() {
+        for q in wrapped_queues(4) {
+            for other in wrapped_queues(3) {
+                let mut expected = q.clone();
+                expected.extend(other.asc_iter().map(|x| x + 10));
+
+                let mut actual = q.clone();
+                let mut other: CircularQueue<usize> = other.clone();
+                for x in other.iter_mut() {
+                    *x += 10;
+                }
+                let evicted = actual.append(&mut other);
+
+                assert_eq!(actual, expected);
+                assert_eq!(evicted as u64, expected.total_evicted() - q.total_evicted());
+                assert!(other.is_empty());
+
+                other.extend(vec![1, 2, 3, 4]);
+                assert_eq!(other, [2, 3, 4]);
+            }
+        }
+    }
+
+    #[cfg(has_const_generics)]
+    #[test]
+    fn append_other_storage() {
+        let mut q = CircularQueue::with_capacity(4);
+        q.extend(vec![1, 2, 3]);
+
+        let mut other = CircularQueue::from_storage(ArrayStorage::<_, 3>::new_array());
+        other.extend(vec![4, 5, 6, 7]);
+        assert_eq!(q.append(&mut other), 2);
+        assert_eq!(q, [3, 5, 6, 7]);
+        assert!(other.is_empty());
+
+        // Elements that didn't fit stay in the other queue.
+        q.set_overflow_policy(OverflowPolicy::Reject);
+        q.clear();
+        q.extend(vec![1, 2]);
+        other.extend(vec![8, 9, 10, 11]);
+        assert_eq!(q.append(&mut other), 0);
+        assert_eq!(q, [1, 2, 9, 10]);
+        assert_eq!(other, [11]);
+        other.push(12);
+        assert_eq!(other, [11, 12]);
+    }
+
+    #[test]
+    fn append_reject() {
+        let mut q = CircularQueue::with_capacity(4);
+        q.set_overflow_policy(OverflowPolicy::Reject);
+        q.extend(vec![1, 2]);
+
+        let mut other = CircularQueue::with_capacity(3);
+        other.extend(vec![3, 4, 5, 6]);
+
+        assert_eq!(q.append(&mut other), 0);
+        assert_eq!(q, [1, 2, 4, 5]);
+        assert_eq!(other, [6]);
+
+        other.push(7);
+        other.push(8);
+        assert_eq!(other, [6, 7, 8]);
+        assert_eq!(q.append(&mut other), 0);
+        assert_eq!(other, [6, 7, 8]);
+    }
+
+    #[test]
+    fn split_off() {
+        for q in wrapped_queues(5) {
+            let asc: Vec<usize> = q.asc_iter().cloned().collect();
+            for at in 0..q.len() + 1 {
+                let mut older = q.clone();
+                let mut newer = older.split_off(at);
+                assert_eq!(older, &asc[..at]);
+                assert_eq!(newer, &asc[at..]);
+                assert_eq!(newer.capacity(), q.capacity());
+
+                // Both halves keep working as queues.
+                let mut expected_older: CircularQueue<usize> = asc[..at].iter().cloned().collect();
+                expected_older.set_capacity(q.capacity());
+                let mut expected_newer: CircularQueue<usize> = asc[at..].iter().cloned().collect();
+                expected_newer.set_capacity(q.capacity());
+                for x in 10..17 {
+                    older.push(x);
+                    newer.push(x);
+                    expected_older.push(x);
+                    expected_newer.push(x);
+                    assert_eq!(older, expected_older);
+                    assert_eq!(newer, expected_newer);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "`at` is out of bounds")]
+    fn split_off_out_of_bounds() {
+        let mut q = CircularQueue::with_capacity(4);
+        q.extend(vec![1, 2]);
+        q.split_off(3);
+    }
+
+    #[test]
+    fn merge_by_key() {
+        let mut a = CircularQueue::with_capacity(3);
+        a.extend(vec![(0, 'a'), (1, 'a'), (3, 'a'), (3, 'b')]);
+        let mut b = CircularQueue::with_capacity(4);
+        b.extend(vec![(2, 'c'), (3, 'c'), (5, 'c')]);
+
+        let merged = a.clone().merge_by_key(b.clone(), 10, |&(t, _)| t);
+        assert_eq!(merged.capacity(), 10);
+        assert_eq!(
+            merged,
+            [(1, 'a'), (2, 'c'), (3, 'a'), (3, 'b'), (3, 'c'), (5, 'c')]
+        );
+
+        let merged = a.clone().merge_by_key(b.clone(), 3, |&(t, _)| t);
+        assert_eq!(merged, [(3, 'b'), (3, 'c'), (5, 'c')]);
+        assert!(merged.is_full());
+
+        let merged = a.merge_by_key(b, 0, |&(t, _)| t);
+        assert!(merged.is_empty());
+    }
+
+    #[test]
+    fn merge_by_key_evictions() {
+        let evicted = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut a = CircularQueue::with_capacity(3);
+        let evicted_in_callback = evicted.clone();
+        a.set_on_evict(move |&x| evicted_in_callback.lock().unwrap().push(x));
+        a.set_overflow_policy(OverflowPolicy::Reject);
+        a.extend(vec![1, 4, 6]);
+
+        let mut b = CircularQueue::with_capacity(4);
+        b.extend(vec![0, 2, 3, 4, 5]);
+
+        let mut merged = a.merge_by_key(b, 4, |&x| x);
+        assert_eq!(merged, [4, 4, 5, 6]);
+        assert_eq!(*evicted.lock().unwrap(), vec![1, 2, 3]);
+        assert_eq!(merged.total_pushed(), 7);
+        assert_eq!(merged.total_evicted(), 3);
+
+        // The overflow policy and the callback carry over.
+        assert_eq!(merged.push(7), Some(7));
+        merged.clear();
+        assert_eq!(*evicted.lock().unwrap(), vec![1, 2, 3, 4, 4, 5, 6]);
+    }
+}
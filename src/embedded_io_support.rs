@@ -0,0 +1,81 @@
+//! `embedded-io` trait implementations for `ByteQueue`.
+
+extern crate embedded_io;
+
+use super::*;
+
+use self::embedded_io::{BufRead, ErrorKind, ErrorType, Read, Write};
+
+impl<S: Storage<u8>> ErrorType for ByteQueue<S> {
+    type Error = ErrorKind;
+}
+
+impl<S: Storage<u8>> Write for ByteQueue<S> {
+    /// Writes the bytes of `buf` and returns the number of bytes written.
+    ///
+    /// Fails with `ErrorKind::WriteZero` if the overflow policy is `OverflowPolicy::Reject` and
+    /// the queue is full.
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize, ErrorKind> {
+        match ByteQueue::write(self, buf) {
+            0 if !buf.is_empty() => Err(ErrorKind::WriteZero),
+            written => Ok(written),
+        }
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), ErrorKind> {
+        Ok(())
+    }
+}
+
+impl<S: Storage<u8>> Read for ByteQueue<S> {
+    /// Reads the oldest unread bytes into `buf`. An empty queue reads as the end of the stream.
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+        Ok(ByteQueue::read(self, buf))
+    }
+}
+
+impl<S: Storage<u8>> BufRead for ByteQueue<S> {
+    /// Returns the oldest contiguous run of unread bytes without copying them.
+    #[inline]
+    fn fill_buf(&mut self) -> Result<&[u8], ErrorKind> {
+        Ok(self.as_slices().0)
+    }
+
+    #[inline]
+    fn consume(&mut self, amount: usize) {
+        ByteQueue::consume(self, amount)
+    }
+}
+
+#[cfg(all(test, has_alloc))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_write() {
+        let mut q = ByteQueue::with_capacity(4);
+        Write::write_all(&mut q, b"abc").unwrap();
+        Write::write_all(&mut q, b"def").unwrap();
+
+        assert_eq!(BufRead::fill_buf(&mut q).unwrap(), b"cd");
+        BufRead::consume(&mut q, 1);
+
+        let mut buf = [0; 4];
+        assert_eq!(Read::read(&mut q, &mut buf).unwrap(), 3);
+        assert_eq!(&buf[..3], b"def");
+        assert_eq!(Read::read(&mut q, &mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn write_zero() {
+        let mut q = ByteQueue::with_capacity(2);
+        q.set_overflow_policy(OverflowPolicy::Reject);
+
+        assert_eq!(Write::write(&mut q, b"abc"), Ok(2));
+        assert_eq!(Write::write(&mut q, b"c"), Err(ErrorKind::WriteZero));
+        assert_eq!(Write::write(&mut q, b""), Ok(0));
+    }
+}
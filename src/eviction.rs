@@ -0,0 +1,126 @@
+//! Bookkeeping of the elements pushed into and evicted from a `CircularQueue`.
+
+use super::*;
+
+/// A callback invoked with every element evicted from a queue.
+// `dyn` requires Rust 1.27.
+#[cfg(has_alloc)]
+#[allow(unknown_lints, bare_trait_objects)]
+pub type EvictCallback<T> = Box<FnMut(&T) + Send>;
+
+/// Counters of the pushed and evicted elements and the eviction callback.
+pub struct Evictions<T> {
+    pushed: u64,
+    evicted: u64,
+    #[cfg(has_alloc)]
+    callback: Option<EvictCallback<T>>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Evictions<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Evictions {
+            pushed: 0,
+            evicted: 0,
+            #[cfg(has_alloc)]
+            callback: None,
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn total_pushed(&self) -> u64 {
+        self.pushed
+    }
+
+    #[inline]
+    pub fn total_evicted(&self) -> u64 {
+        self.evicted
+    }
+
+    #[cfg(has_alloc)]
+    #[inline]
+    pub fn set_callback(&mut self, callback: Option<EvictCallback<T>>) {
+        self.callback = callback;
+    }
+
+    #[cfg(has_alloc)]
+    #[inline]
+    pub fn has_callback(&self) -> bool {
+        self.callback.is_some()
+    }
+
+    #[cfg(not(has_alloc))]
+    #[inline]
+    pub fn has_callback(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    pub fn pushed(&mut self, count: usize) {
+        self.pushed += count as u64;
+    }
+
+    /// Records the eviction of `count` elements without invoking the callback.
+    ///
+    /// Only used when there is no callback or the callback was already invoked for each of them.
+    #[inline]
+    pub fn evicted(&mut self, count: usize) {
+        self.evicted += count as u64;
+    }
+
+    /// Records the eviction of `x` and invokes the callback with it.
+    #[inline]
+    pub fn evict(&mut self, x: &T) {
+        self.evicted += 1;
+        self.call(x);
+    }
+
+    #[cfg(has_alloc)]
+    #[inline]
+    fn call(&mut self, x: &T) {
+        if let Some(ref mut callback) = self.callback {
+            callback(x);
+        }
+    }
+
+    #[cfg(not(has_alloc))]
+    #[inline]
+    fn call(&mut self, _x: &T) {}
+
+    /// Records the eviction of all elements of `items` in order and invokes the callback with
+    /// each of them.
+    #[inline]
+    pub fn evict_all<'a, I>(&mut self, items: I)
+    where
+        I: IntoIterator<Item = &'a T>,
+        T: 'a,
+    {
+        if self.has_callback() {
+            for x in items {
+                self.evict(x);
+            }
+        } else {
+            self.evicted(items.into_iter().count());
+        }
+    }
+}
+
+// The callback is only ever accessed through `&mut self`, so sharing `&Evictions` between threads
+// can't call it concurrently and it doesn't need to be `Sync`.
+unsafe impl<T: Sync> Sync for Evictions<T> {}
+
+impl<T> Clone for Evictions<T> {
+    /// Clones the counters. The callback is not cloned.
+    #[inline]
+    fn clone(&self) -> Self {
+        Evictions {
+            pushed: self.pushed,
+            evicted: self.evicted,
+            #[cfg(has_alloc)]
+            callback: None,
+            _marker: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,313 @@
+//! A queue read by several independent readers.
+
+use super::*;
+
+/// Identifies a reader of a `FanoutQueue`.
+///
+/// Identifiers are never reused within a queue, so an identifier of a removed reader stays
+/// invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ReaderId(usize);
+
+/// A queue written by one writer and read by several readers, each at its own pace.
+///
+/// Every reader has a cursor pointing past the last item it has read. `read()` returns the items
+/// the reader hasn't seen yet, going from the oldest ones to the newest ones, and moves its cursor
+/// to the newest end. The writer never waits for the readers: when the queue is full, `push()`
+/// overwrites the oldest item even if some readers haven't seen it, and those readers are told how
+/// many items they missed.
+///
+/// # Examples
+///
+/// ```
+/// use circular_queue::FanoutQueue;
+///
+/// let mut events = FanoutQueue::with_capacity(3);
+/// let fast = events.add_reader();
+/// let slow = events.add_reader();
+///
+/// events.push("a");
+/// events.push("b");
+/// assert_eq!(events.read(fast).unwrap().collect::<Vec<_>>(), [&"a", &"b"]);
+///
+/// events.push("c");
+/// events.push("d");
+/// events.push("e");
+/// assert_eq!(events.read(fast).unwrap().collect::<Vec<_>>(), [&"c", &"d", &"e"]);
+///
+/// // The slow reader missed "a" and "b".
+/// let read = events.read(slow).unwrap();
+/// assert_eq!(read.lagged(), 2);
+/// assert_eq!(read.collect::<Vec<_>>(), [&"c", &"d", &"e"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct FanoutQueue<T> {
+    queue: CircularQueue<T>,
+    // The readers and the number of pushed items each of them has seen, in order of their ids.
+    readers: Vec<(ReaderId, u64)>,
+    next_reader: usize,
+}
+
+/// An iterator over the items a reader of a `FanoutQueue` hasn't seen yet, created with
+/// `FanoutQueue::read()`.
+///
+/// The iterator goes from the oldest items to the newest ones.
+#[derive(Debug)]
+pub struct FanoutRead<'a, T: 'a> {
+    iter: RangeIter<'a, T>,
+    lagged: u64,
+}
+
+impl<'a, T> Clone for FanoutRead<'a, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        FanoutRead {
+            iter: self.iter.clone(),
+            lagged: self.lagged,
+        }
+    }
+}
+
+impl<'a, T> FanoutRead<'a, T> {
+    /// Returns the number of items that were overwritten before the reader saw them.
+    #[inline]
+    pub fn lagged(&self) -> u64 {
+        self.lagged
+    }
+}
+
+impl<'a, T> Iterator for FanoutRead<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for FanoutRead<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for FanoutRead<'a, T> {}
+
+impl<T> FanoutQueue<T> {
+    /// Constructs a new, empty `FanoutQueue` with the requested capacity and no readers.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        FanoutQueue {
+            queue: CircularQueue::with_capacity(capacity),
+            readers: Vec::new(),
+            next_reader: 0,
+        }
+    }
+
+    /// Returns the underlying queue.
+    #[inline]
+    pub fn queue(&self) -> &CircularQueue<T> {
+        &self.queue
+    }
+
+    /// Returns the number of items in the queue.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if the queue contains no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Returns the capacity of the queue.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+
+    /// Pushes a new item into the queue.
+    ///
+    /// If the queue is full, the oldest item is overwritten and returned, whether or not all
+    /// readers have seen it.
+    #[inline]
+    pub fn push(&mut self, x: T) -> Popped<T> {
+        self.queue.push(x)
+    }
+
+    /// Registers a new reader and returns its identifier.
+    ///
+    /// The reader only sees the items pushed after it was added.
+    #[inline]
+    pub fn add_reader(&mut self) -> ReaderId {
+        let id = ReaderId(self.next_reader);
+        self.next_reader += 1;
+        self.readers.push((id, self.queue.total_pushed()));
+        id
+    }
+
+    /// Unregisters a reader. Returns `false` if there was no such reader.
+    #[inline]
+    pub fn remove_reader(&mut self, reader: ReaderId) -> bool {
+        match self.cursor_index(reader) {
+            Some(index) => {
+                self.readers.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the number of registered readers.
+    #[inline]
+    pub fn reader_count(&self) -> usize {
+        self.readers.len()
+    }
+
+    /// Returns the index of the reader in `readers`.
+    #[inline]
+    fn cursor_index(&self, reader: ReaderId) -> Option<usize> {
+        self.readers
+            .binary_search_by_key(&reader, |&(id, _)| id)
+            .ok()
+    }
+
+    /// Returns the number of pushed items before the oldest one in the queue.
+    #[inline]
+    fn oldest_position(&self) -> u64 {
+        self.queue.total_pushed() - self.queue.len() as u64
+    }
+
+    /// Returns the number of items in the queue the reader hasn't seen yet, or `None` if there is
+    /// no such reader.
+    #[inline]
+    pub fn unread(&self, reader: ReaderId) -> Option<usize> {
+        self.cursor_index(reader).map(|index| {
+            let cursor = cmp::max(self.readers[index].1, self.oldest_position());
+            (self.queue.total_pushed() - cursor) as usize
+        })
+    }
+
+    /// Returns the number of items that were overwritten before the reader saw them, or `None` if
+    /// there is no such reader.
+    #[inline]
+    pub fn lag(&self, reader: ReaderId) -> Option<u64> {
+        self.cursor_index(reader)
+            .map(|index| self.oldest_position().saturating_sub(self.readers[index].1))
+    }
+
+    /// Returns the items the reader hasn't seen yet, going from the oldest ones to the newest
+    /// ones, or `None` if there is no such reader.
+    ///
+    /// All of the returned items count as seen by the reader, even if the iterator isn't
+    /// consumed. The number of items the reader missed because they were overwritten is available
+    /// through `FanoutRead::lagged()`.
+    #[inline]
+    pub fn read<'a>(&'a mut self, reader: ReaderId) -> Option<FanoutRead<'a, T>> {
+        match self.cursor_index(reader) {
+            Some(index) => {
+                let oldest = self.oldest_position();
+                let cursor = replace(&mut self.readers[index].1, self.queue.total_pushed());
+
+                let start = (cmp::max(cursor, oldest) - oldest) as usize;
+                Some(FanoutRead {
+                    iter: self.queue.range_iter(start, self.queue.len()),
+                    lagged: oldest.saturating_sub(cursor),
+                })
+            }
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(has_extern_crate_alloc)]
+    use alloc::vec;
+
+    fn read(q: &mut FanoutQueue<i32>, reader: ReaderId) -> (u64, Vec<i32>) {
+        let read = q.read(reader).unwrap();
+        (read.lagged(), read.cloned().collect())
+    }
+
+    #[test]
+    fn readers() {
+        let mut q = FanoutQueue::with_capacity(4);
+        q.push(0);
+
+        let a = q.add_reader();
+        assert_eq!(read(&mut q, a), (0, vec![]));
+
+        q.push(1);
+        q.push(2);
+        let b = q.add_reader();
+        q.push(3);
+
+        assert_eq!(q.unread(a), Some(3));
+        assert_eq!(q.unread(b), Some(1));
+        assert_eq!(read(&mut q, a), (0, vec![1, 2, 3]));
+        assert_eq!(read(&mut q, a), (0, vec![]));
+
+        for x in 4..10 {
+            q.push(x);
+        }
+        assert_eq!(q.lag(a), Some(2));
+        assert_eq!(q.lag(b), Some(3));
+        assert_eq!(q.unread(b), Some(4));
+        assert_eq!(read(&mut q, b), (3, vec![6, 7, 8, 9]));
+        assert_eq!(q.lag(b), Some(0));
+        assert_eq!(read(&mut q, a), (2, vec![6, 7, 8, 9]));
+
+        assert!(q.remove_reader(a));
+        assert!(!q.remove_reader(a));
+        assert!(q.read(a).is_none());
+        assert_eq!(q.unread(a), None);
+        assert_eq!(q.reader_count(), 1);
+
+        // Identifiers aren't reused.
+        let c = q.add_reader();
+        assert_ne!(c, a);
+        q.push(10);
+        assert_eq!(read(&mut q, c), (0, vec![10]));
+        assert_eq!(read(&mut q, b), (0, vec![10]));
+    }
+
+    #[test]
+    fn clone_read() {
+        // The items don't need to implement `Clone`.
+        #[derive(Debug, PartialEq)]
+        struct Item(i32);
+
+        let mut q = FanoutQueue::with_capacity(2);
+        let a = q.add_reader();
+        q.push(Item(1));
+        q.push(Item(2));
+        q.push(Item(3));
+
+        let mut read = q.read(a).unwrap();
+        assert_eq!(read.next(), Some(&Item(2)));
+        let copy = read.clone();
+        assert_eq!(copy.lagged(), 1);
+        assert_eq!(copy.collect::<Vec<_>>(), [&Item(3)]);
+        assert_eq!(read.collect::<Vec<_>>(), [&Item(3)]);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut q = FanoutQueue::with_capacity(0);
+        let a = q.add_reader();
+        q.push(1);
+        q.push(2);
+        assert_eq!(q.unread(a), Some(0));
+        assert_eq!(read(&mut q, a), (2, vec![]));
+    }
+}
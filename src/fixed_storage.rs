@@ -0,0 +1,432 @@
+//! Fixed-capacity storages on top of uninitialized memory.
+
+use super::*;
+
+use core::mem::MaybeUninit;
+use core::{ptr, slice};
+
+/// A buffer of possibly uninitialized slots for `UninitStorage`.
+///
+/// # Safety
+///
+/// Both methods must return the same memory of the same length every time they are called.
+pub unsafe trait UninitBuffer<T> {
+    /// Returns the slots.
+    fn as_uninit_slice(&self) -> &[MaybeUninit<T>];
+
+    /// Returns the slots.
+    fn as_uninit_mut_slice(&mut self) -> &mut [MaybeUninit<T>];
+}
+
+unsafe impl<T, const N: usize> UninitBuffer<T> for [MaybeUninit<T>; N] {
+    #[inline]
+    fn as_uninit_slice(&self) -> &[MaybeUninit<T>] {
+        self
+    }
+
+    #[inline]
+    fn as_uninit_mut_slice(&mut self) -> &mut [MaybeUninit<T>] {
+        self
+    }
+}
+
+unsafe impl<T> UninitBuffer<T> for &mut [MaybeUninit<T>] {
+    #[inline]
+    fn as_uninit_slice(&self) -> &[MaybeUninit<T>] {
+        self
+    }
+
+    #[inline]
+    fn as_uninit_mut_slice(&mut self) -> &mut [MaybeUninit<T>] {
+        self
+    }
+}
+
+#[cfg(has_alloc)]
+unsafe impl<T> UninitBuffer<T> for Box<[MaybeUninit<T>]> {
+    #[inline]
+    fn as_uninit_slice(&self) -> &[MaybeUninit<T>] {
+        self
+    }
+
+    #[inline]
+    fn as_uninit_mut_slice(&mut self) -> &mut [MaybeUninit<T>] {
+        self
+    }
+}
+
+/// A fixed-capacity `Storage` in a buffer of uninitialized slots.
+///
+/// The first `len` slots are initialized, the rest are not. The initialized elements are dropped
+/// together with the storage.
+pub struct UninitStorage<T, B: UninitBuffer<T>> {
+    buf: B,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+/// A `Storage` in an inline array, requiring no allocation.
+pub type ArrayStorage<T, const N: usize> = UninitStorage<T, [MaybeUninit<T>; N]>;
+
+/// A `Storage` in a borrowed slice, such as a buffer in a static memory region.
+pub type SliceStorage<'a, T> = UninitStorage<T, &'a mut [MaybeUninit<T>]>;
+
+/// A `Storage` in a boxed slice that never reallocates.
+#[cfg(has_alloc)]
+pub type BoxedStorage<T> = UninitStorage<T, Box<[MaybeUninit<T>]>>;
+
+impl<T, B: UninitBuffer<T>> UninitStorage<T, B> {
+    /// Creates an empty storage in the provided buffer.
+    #[inline]
+    pub fn new(buf: B) -> Self {
+        Self {
+            buf,
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, const N: usize> ArrayStorage<T, N> {
+    /// Creates an empty storage in an inline array.
+    #[inline]
+    pub fn new_array() -> Self {
+        // An array of `MaybeUninit` doesn't need initialization.
+        let buf = unsafe { MaybeUninit::<[MaybeUninit<T>; N]>::uninit().assume_init() };
+        Self::new(buf)
+    }
+}
+
+impl<T, const N: usize> Default for ArrayStorage<T, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new_array()
+    }
+}
+
+#[cfg(has_alloc)]
+impl<T> BoxedStorage<T> {
+    /// Creates an empty storage in a newly allocated boxed slice with the requested capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let buf = (0..capacity).map(|_| MaybeUninit::uninit()).collect();
+        Self::new(buf)
+    }
+}
+
+#[cfg(has_alloc)]
+impl<T> From<Box<[T]>> for BoxedStorage<T> {
+    /// Converts a boxed slice into a full storage, reusing the allocation.
+    #[inline]
+    fn from(buf: Box<[T]>) -> Self {
+        let len = buf.len();
+        // MaybeUninit<T> has the same layout as T.
+        let buf = unsafe { Box::from_raw(Box::into_raw(buf) as *mut [MaybeUninit<T>]) };
+        Self {
+            buf,
+            len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, B: UninitBuffer<T>> Storage<T> for UninitStorage<T, B> {
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        let ptr = self.buf.as_uninit_slice().as_ptr() as *const T;
+        unsafe { slice::from_raw_parts(ptr, self.len) }
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        let ptr = self.buf.as_uninit_mut_slice().as_mut_ptr() as *mut T;
+        unsafe { slice::from_raw_parts_mut(ptr, self.len) }
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.buf.as_uninit_slice().len()
+    }
+
+    #[inline]
+    fn reserve_exact(&mut self, additional: usize) {
+        assert!(
+            additional <= self.capacity() - self.len,
+            "fixed-capacity storage cannot grow"
+        );
+    }
+
+    #[inline]
+    fn push(&mut self, value: T) {
+        let len = self.len;
+        let slot = &mut self.buf.as_uninit_mut_slice()[len];
+        *slot = MaybeUninit::new(value);
+        self.len += 1;
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+        let len = self.len;
+        let slot = &self.buf.as_uninit_slice()[len];
+        Some(unsafe { ptr::read(slot.as_ptr()) })
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+
+        let tail: *mut [T] = &mut self.as_mut_slice()[len..];
+        // Update the length first so that a panicking destructor doesn't cause a double drop.
+        self.len = len;
+        unsafe { ptr::drop_in_place(tail) };
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, values: &[T])
+    where
+        T: Copy,
+    {
+        let len = self.len;
+        let slots = &mut self.buf.as_uninit_mut_slice()[len..len + values.len()];
+        let ptr = slots.as_mut_ptr() as *mut T;
+        unsafe { ptr::copy_nonoverlapping(values.as_ptr(), ptr, values.len()) };
+        self.len += values.len();
+    }
+}
+
+impl<T, B: UninitBuffer<T>> Drop for UninitStorage<T, B> {
+    #[inline]
+    fn drop(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayStorage<T, N> {
+    fn clone(&self) -> Self {
+        let mut storage = Self::new_array();
+        for x in self.as_slice() {
+            storage.push(x.clone());
+        }
+        storage
+    }
+}
+
+#[cfg(has_alloc)]
+impl<T: Clone> Clone for BoxedStorage<T> {
+    fn clone(&self) -> Self {
+        let mut storage = Self::with_capacity(self.capacity());
+        for x in self.as_slice() {
+            storage.push(x.clone());
+        }
+        storage
+    }
+}
+
+impl<T: fmt::Debug, B: UninitBuffer<T>> fmt::Debug for UninitStorage<T, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+/// A `CircularQueue<T>` on top of a caller-provided buffer of uninitialized slots.
+///
+/// It needs neither `alloc` nor `std`, which makes it usable with buffers in statically placed
+/// memory regions. The elements left in the queue are dropped together with it, while the buffer
+/// itself is left to the caller.
+pub type CircularQueueRef<'a, T> = CircularQueue<T, SliceStorage<'a, T>>;
+
+impl<'a, T> CircularQueueRef<'a, T> {
+    /// Constructs a new, empty queue in the provided buffer, with capacity equal to its length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueueRef;
+    /// use std::mem::MaybeUninit;
+    ///
+    /// let mut buf = [MaybeUninit::uninit(); 3];
+    /// let mut queue = CircularQueueRef::from_buffer(&mut buf);
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.push(3);
+    /// queue.push(4);
+    ///
+    /// assert!(queue.is_full());
+    /// assert_eq!(queue.iter().collect::<Vec<_>>(), [&4, &3, &2]);
+    /// ```
+    #[inline]
+    pub fn from_buffer(buf: &'a mut [MaybeUninit<T>]) -> Self {
+        Self::from_storage(SliceStorage::new(buf))
+    }
+}
+
+#[cfg(all(test, has_alloc))]
+mod tests {
+    use super::*;
+    use alloc::rc::Rc;
+    use alloc::vec;
+
+    /// Runs the same sequence of operations on a queue with capacity 3 and checks the results.
+    fn check_ring<S: Storage<i32>>(mut q: CircularQueue<i32, S>) {
+        assert_eq!(q.capacity(), 3);
+        assert!(q.is_empty());
+
+        assert_eq!(q.push(1), None);
+        assert_eq!(q.push(2), None);
+        assert_eq!(q, vec![1, 2]);
+        assert_eq!(q.iter().cloned().collect::<Vec<_>>(), vec![2, 1]);
+
+        assert_eq!(q.push(3), None);
+        assert!(q.is_full());
+        assert_eq!(q.push(4), Some(1));
+        assert_eq!(q.push(5), Some(2));
+        assert_eq!(q, vec![3, 4, 5]);
+        assert_eq!(q.iter().cloned().collect::<Vec<_>>(), vec![5, 4, 3]);
+
+        for x in q.asc_iter_mut() {
+            *x *= 10;
+        }
+        assert_eq!(q, vec![30, 40, 50]);
+
+        q.clear();
+        assert!(q.is_empty());
+        q.push(6);
+        assert_eq!(q, vec![6]);
+    }
+
+    #[test]
+    fn all_storages() {
+        check_ring(CircularQueue::with_capacity(3));
+        check_ring(CircularQueue::from_storage(
+            ArrayStorage::<i32, 3>::new_array(),
+        ));
+        check_ring(CircularQueue::from_storage(BoxedStorage::with_capacity(3)));
+
+        let mut buf = [MaybeUninit::uninit(); 3];
+        check_ring(CircularQueue::from_storage(SliceStorage::new(&mut buf[..])));
+    }
+
+    #[test]
+    fn circular_queue_ref() {
+        let rc = Rc::new(());
+
+        let mut buf: [MaybeUninit<Rc<()>>; 3] = [
+            MaybeUninit::uninit(),
+            MaybeUninit::uninit(),
+            MaybeUninit::uninit(),
+        ];
+        {
+            let mut q = CircularQueueRef::from_buffer(&mut buf);
+            assert_eq!(q.capacity(), 3);
+
+            for _ in 0..5 {
+                q.push(rc.clone());
+            }
+            assert!(q.is_full());
+            assert_eq!(q.iter().count(), 3);
+            assert_eq!(q.asc_iter().count(), 3);
+            assert_eq!(Rc::strong_count(&rc), 4);
+        }
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        // The buffer can be reused after the queue is gone.
+        let mut q = CircularQueueRef::from_buffer(&mut buf);
+        assert!(q.is_empty());
+        q.push(rc.clone());
+        assert_eq!(Rc::strong_count(&rc), 2);
+    }
+
+    #[test]
+    fn write_from_slice() {
+        let mut q = CircularQueue::from_storage(ArrayStorage::<u8, 4>::new_array());
+        assert_eq!(q.write_from_slice(b"ab"), 2);
+        assert_eq!(q.write_from_slice(b"cdef"), 4);
+        assert_eq!(q, b"cdef".to_vec());
+
+        let mut buf = [0; 4];
+        q.copy_to_slice_asc(&mut buf);
+        assert_eq!(&buf, b"cdef");
+    }
+
+    #[test]
+    fn from_boxed_slice() {
+        let buf: Box<[i32]> = vec![1, 2, 3].into_boxed_slice();
+        let mut q = CircularQueue::from_storage(BoxedStorage::from(buf));
+        assert!(q.is_full());
+        assert_eq!(q, vec![1, 2, 3]);
+
+        q.push(4);
+        assert_eq!(q, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn drops_elements() {
+        let rc = Rc::new(());
+
+        {
+            let mut q = CircularQueue::from_storage(ArrayStorage::<_, 3>::new_array());
+            for _ in 0..5 {
+                q.push(rc.clone());
+            }
+            assert_eq!(Rc::strong_count(&rc), 4);
+
+            q.clear();
+            assert_eq!(Rc::strong_count(&rc), 1);
+
+            q.push(rc.clone());
+            q.push(rc.clone());
+            assert_eq!(Rc::strong_count(&rc), 3);
+        }
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        {
+            let mut buf: Vec<MaybeUninit<Rc<()>>> = (0..4).map(|_| MaybeUninit::uninit()).collect();
+            let mut q = CircularQueue::from_storage(SliceStorage::new(&mut buf[..]));
+            q.push(rc.clone());
+            q.push(rc.clone());
+            assert_eq!(Rc::strong_count(&rc), 3);
+        }
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn clone() {
+        let mut q = CircularQueue::from_storage(ArrayStorage::<_, 3>::new_array());
+        q.push(1);
+        q.push(2);
+        q.push(3);
+        q.push(4);
+
+        let c = q.clone();
+        assert!(c.eq_capacity(&q));
+        assert_eq!(c, vec![2, 3, 4]);
+
+        let mut q = CircularQueue::from_storage(BoxedStorage::with_capacity(2));
+        q.push(1);
+        let c = q.clone();
+        assert!(c.eq_capacity(&q));
+    }
+
+    #[test]
+    fn zero_sized() {
+        let mut q = CircularQueue::from_storage(ArrayStorage::<(), 2>::new_array());
+        q.push(());
+        q.push(());
+        q.push(());
+        assert_eq!(q.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "fixed-capacity storage cannot grow")]
+    fn cannot_grow() {
+        let mut storage = ArrayStorage::<i32, 2>::new_array();
+        storage.reserve_exact(3);
+    }
+}
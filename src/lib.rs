@@ -7,10 +7,44 @@
 //! oldest items to the newest ones.
 //!
 //! Two queues are considered equal if iterating over them with `iter()` would yield the same
-//! sequence of elements.
+//! sequence of elements. Likewise, queues are hashed and ordered (lexicographically) by the
+//! sequence `iter()` yields, so the capacity and the internal layout don't matter.
+//!
+//! A queue can also be compared with slices, arrays, `Vec`s and `VecDeque`s. Those are considered
+//! to go from the oldest items to the newest ones, like `asc_iter()`.
+//!
+//! The elements are stored in a `Vec<T>` by default. Any other `Storage` can be used through
+//! `CircularQueue::from_storage()`, such as an inline array (`ArrayStorage`), a borrowed buffer
+//! (`SliceStorage`) or a boxed slice that never reallocates (`BoxedStorage`).
+//!
+//! The `alloc` feature is enabled by default. Disabling it removes the dependency on the `alloc`
+//! crate along with everything that needs an allocation, leaving `CircularQueueRef` on top of a
+//! caller-provided buffer and the other fixed-capacity storages.
+//!
+//! `ByteQueue` turns a queue of bytes into a stream that is read from the oldest end. With the
+//! `std` feature it implements `io::Read`, `io::BufRead` and `io::Write`, and with the
+//! `embedded_io` feature it implements the [`embedded-io`](https://crates.io/crates/embedded-io)
+//! traits. Queues of bytes and characters also implement `fmt::Write`, so log messages can be
+//! formatted into them with `write!` even without `std`.
+//!
+//! `FanoutQueue` lets several readers consume the same queue at their own pace, each with its own
+//! cursor.
+//!
+//! `RecentSet` keeps the most recently used distinct elements, moving an element to the newest
+//! position when it's pushed again. `LruRing` builds on it to keep the most recently used
+//! key-value pairs, evicting the least recently used one when full.
 //!
 //! Enable the `serde_support` feature for [Serde](https://serde.rs/) support.
 //!
+//! Enable the `allocator_api` feature to allocate the queue with a custom allocator through
+//! [`allocator-api2`](https://crates.io/crates/allocator-api2), see `CircularQueueIn`.
+//!
+//! Enable the `log_support` or `tracing_support` feature to keep the most recent log records in
+//! memory with `LogBuffer`.
+//!
+//! Enable the `shm` feature on Unix for a queue shared between processes through a memory-mapped
+//! file, see the `shm` module.
+//!
 //! # Examples
 //!
 //! ```
@@ -34,36 +68,207 @@
 #![cfg_attr(has_extern_crate_alloc, no_std)]
 #![doc(html_root_url = "https://docs.rs/circular-queue/0.2.7")]
 
-#[cfg(has_extern_crate_alloc)]
+#[cfg(all(has_extern_crate_alloc, feature = "alloc"))]
 extern crate alloc;
-
-#[cfg(has_extern_crate_alloc)]
+#[cfg(all(has_extern_crate_alloc, any(feature = "std", test)))]
+extern crate std;
+
+#[cfg(all(has_extern_crate_alloc, feature = "alloc"))]
+use alloc::boxed::Box;
+#[cfg(all(has_extern_crate_alloc, feature = "alloc", has_try_reserve))]
+use alloc::collections::TryReserveError;
+#[cfg(all(has_extern_crate_alloc, feature = "alloc"))]
+use alloc::collections::VecDeque;
+#[cfg(all(has_extern_crate_alloc, feature = "alloc"))]
 use alloc::vec::Vec;
 #[cfg(has_extern_crate_alloc)]
+use core::cmp::{self, Ordering};
+#[cfg(has_extern_crate_alloc)]
+use core::fmt;
+#[cfg(has_extern_crate_alloc)]
+use core::hash::{Hash, Hasher};
+#[cfg(all(has_extern_crate_alloc, feature = "alloc"))]
+use core::iter::FromIterator;
+#[cfg(has_extern_crate_alloc)]
 use core::iter::{Chain, Rev};
 #[cfg(has_extern_crate_alloc)]
-use core::mem::replace;
+use core::marker::PhantomData;
+#[cfg(has_extern_crate_alloc)]
+use core::mem::{forget, replace};
+#[cfg(has_extern_crate_alloc)]
+use core::ptr;
 #[cfg(has_extern_crate_alloc)]
 use core::slice::{Iter as SliceIter, IterMut as SliceIterMut};
 
 #[cfg(not(has_extern_crate_alloc))]
-use std::iter::{Chain, Rev};
+use std::cmp::{self, Ordering};
+#[cfg(all(not(has_extern_crate_alloc), has_try_reserve))]
+use std::collections::TryReserveError;
+#[cfg(not(has_extern_crate_alloc))]
+use std::collections::VecDeque;
+#[cfg(not(has_extern_crate_alloc))]
+use std::fmt;
+#[cfg(not(has_extern_crate_alloc))]
+use std::hash::{Hash, Hasher};
 #[cfg(not(has_extern_crate_alloc))]
-use std::mem::replace;
+use std::iter::{Chain, FromIterator, Rev};
+#[cfg(not(has_extern_crate_alloc))]
+use std::marker::PhantomData;
+#[cfg(not(has_extern_crate_alloc))]
+use std::mem::{forget, replace};
+#[cfg(not(has_extern_crate_alloc))]
+use std::ptr;
 #[cfg(not(has_extern_crate_alloc))]
 use std::slice::{Iter as SliceIter, IterMut as SliceIterMut};
 
+#[cfg(has_const_generics)]
+mod array_impls;
+#[cfg(has_const_generics)]
+pub use array_impls::ArrayWindows;
+
+mod byte_queue;
+pub use byte_queue::ByteQueue;
+
+#[cfg(has_alloc)]
+mod builder;
+#[cfg(has_alloc)]
+pub use builder::CircularQueueBuilder;
+
+#[cfg(has_alloc)]
+mod combine;
+
+#[cfg(has_alloc)]
+mod fanout;
+#[cfg(has_alloc)]
+pub use fanout::{FanoutQueue, FanoutRead, ReaderId};
+
+#[cfg(has_const_generics)]
+mod fixed_storage;
+#[cfg(all(has_const_generics, has_alloc))]
+pub use fixed_storage::BoxedStorage;
+#[cfg(has_const_generics)]
+pub use fixed_storage::{
+    ArrayStorage, CircularQueueRef, SliceStorage, UninitBuffer, UninitStorage,
+};
+
+mod eviction;
+use eviction::Evictions;
+
+#[cfg(has_alloc)]
+mod lru;
+#[cfg(all(has_alloc, any(feature = "std", not(has_extern_crate_alloc))))]
+pub use lru::HashLruRing;
+#[cfg(has_alloc)]
+pub use lru::LruRing;
+
+#[cfg(has_alloc)]
+mod recent_set;
+#[cfg(all(has_alloc, any(feature = "std", not(has_extern_crate_alloc))))]
+pub use recent_set::{HashIndex, HashRecentSet};
+#[cfg(has_alloc)]
+pub use recent_set::{LinearIndex, RecentSet, RecentSetIndex};
+
+mod reorder;
+
+mod search;
+
+mod selector;
+pub use selector::{EvictLowestByKey, EvictOldest, EvictWith, EvictionSelector};
+
+mod storage;
+#[cfg(feature = "allocator_api")]
+pub use storage::CircularQueueIn;
+pub use storage::Storage;
+
+#[cfg(feature = "embedded_io")]
+mod embedded_io_support;
+
+#[cfg(any(feature = "log_support", feature = "tracing_support"))]
+mod log_buffer;
+#[cfg(any(feature = "log_support", feature = "tracing_support"))]
+pub use log_buffer::{LogBuffer, LogLevel, LogRecord};
+
 #[cfg(feature = "serde_support")]
 mod serde_support;
 
+mod text;
+pub use text::Utf8Dump;
+
+#[cfg(all(feature = "shm", unix))]
+pub mod shm;
+
+mod range;
+pub use range::{RangeIter, RangeIterMut};
+
+mod windows;
+pub use windows::{Chunks, Pairs, Window, Windows};
+
 /// A circular buffer-like queue.
-#[derive(Clone, Debug)]
-pub struct CircularQueue<T> {
-    data: Vec<T>,
+///
+/// The elements are kept in a `Vec<T>` by default. The second type parameter allows using a
+/// different [`Storage`](trait.Storage.html).
+///
+/// Cloning a queue clones its elements, overflow policy and counters, but not the eviction
+/// callback set with `set_on_evict()`, so the clone starts without one.
+///
+/// Since the eviction callback is called with `&T`, the queue is invariant over `T`. For example,
+/// a `CircularQueue<&'static str>` can't be used where a `CircularQueue<&'a str>` is expected.
+#[cfg(has_alloc)]
+#[derive(Clone)]
+pub struct CircularQueue<T, S = Vec<T>> {
+    data: S,
     // Using our own capacity instead of the one stored in Vec to ensure consistent behavior with
     // zero-sized types.
     capacity: usize,
     insertion_index: usize,
+    overflow_policy: OverflowPolicy,
+    evictions: Evictions<T>,
+}
+
+/// A circular buffer-like queue.
+///
+/// Without the `alloc` feature there is no default storage, so the elements are kept in the
+/// [`Storage`](trait.Storage.html) given by the second type parameter.
+#[cfg(not(has_alloc))]
+#[derive(Clone)]
+pub struct CircularQueue<T, S> {
+    data: S,
+    capacity: usize,
+    insertion_index: usize,
+    overflow_policy: OverflowPolicy,
+    evictions: Evictions<T>,
+}
+
+/// What `CircularQueue::push()` does when the queue is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// Overwrite the oldest element and return it. This is the default.
+    Overwrite,
+    /// Leave the queue unchanged and return the pushed element.
+    Reject,
+}
+
+impl Default for OverflowPolicy {
+    #[inline]
+    fn default() -> Self {
+        OverflowPolicy::Overwrite
+    }
+}
+
+/// When `CircularQueue` allocates memory for its elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Preallocation {
+    /// Allocate memory for the whole capacity upfront. This is the default.
+    Eager,
+    /// Grow the allocation as elements are pushed, up to the capacity.
+    Lazy,
+}
+
+impl Default for Preallocation {
+    #[inline]
+    fn default() -> Self {
+        Preallocation::Eager
+    }
 }
 
 /// An iterator over `CircularQueue<T>`.
@@ -81,6 +286,7 @@ pub type AscIterMut<'a, T> = Chain<SliceIterMut<'a, T>, SliceIterMut<'a, T>>;
 /// A value popped from `CircularQueue<T>` as the result of a push operation.
 pub type Popped<T> = Option<T>;
 
+#[cfg(has_alloc)]
 impl<T> CircularQueue<T> {
     /// Constructs a new, empty `CircularQueue<T>` with the requested capacity.
     ///
@@ -97,6 +303,139 @@ impl<T> CircularQueue<T> {
             data: Vec::with_capacity(capacity),
             capacity,
             insertion_index: 0,
+            overflow_policy: OverflowPolicy::Overwrite,
+            evictions: Evictions::new(),
+        }
+    }
+
+    /// Constructs a new, empty `CircularQueue<T>` with the requested capacity, returning an error
+    /// if the allocation fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let queue = CircularQueue::<i32>::try_with_capacity(5).unwrap();
+    /// assert_eq!(queue.capacity(), 5);
+    ///
+    /// assert!(CircularQueue::<i32>::try_with_capacity(usize::MAX).is_err());
+    /// ```
+    #[cfg(has_try_reserve)]
+    #[inline]
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+        let mut queue = Self::with_capacity(0);
+        queue.data.try_reserve_exact(capacity)?;
+        queue.capacity = capacity;
+        Ok(queue)
+    }
+
+    /// Returns a builder for configuring a new `CircularQueue<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::{CircularQueue, OverflowPolicy, Preallocation};
+    ///
+    /// let mut queue = CircularQueue::builder()
+    ///     .capacity(3)
+    ///     .overflow_policy(OverflowPolicy::Reject)
+    ///     .preallocation(Preallocation::Lazy)
+    ///     .contents(vec![1, 2])
+    ///     .build();
+    ///
+    /// assert_eq!(queue.push(3), None);
+    /// assert_eq!(queue.push(4), Some(4));
+    /// assert_eq!(queue, vec![1, 2, 3]);
+    /// ```
+    #[inline]
+    pub fn builder() -> CircularQueueBuilder<T> {
+        CircularQueueBuilder::new()
+    }
+
+    /// Constructs a new `CircularQueue<T>` with the requested capacity, containing the items of
+    /// `iter`.
+    ///
+    /// The items are pushed in order, so if there are more of them than the capacity, only the
+    /// last ones are kept. The construction doesn't count towards `total_pushed()` and
+    /// `total_evicted()`, which start at zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let queue = CircularQueue::from_iter_with_capacity(1..6, 3);
+    ///
+    /// assert_eq!(queue.capacity(), 3);
+    /// assert_eq!(queue, vec![3, 4, 5]);
+    /// ```
+    #[inline]
+    pub fn from_iter_with_capacity<I: IntoIterator<Item = T>>(iter: I, capacity: usize) -> Self {
+        let mut queue = Self::with_capacity(capacity);
+        queue.extend(iter);
+        // There is no eviction callback yet, so only the counters have to be reset.
+        queue.evictions = Evictions::new();
+        queue
+    }
+}
+
+/// A slot of a full queue whose element was moved out by `push_with()`.
+///
+/// Dropping it removes the slot from the queue. It's forgotten once the slot is written to again.
+struct Hole<'a, T: 'a, S: 'a + Storage<T>> {
+    queue: &'a mut CircularQueue<T, S>,
+    index: usize,
+}
+
+impl<'a, T, S: Storage<T>> Drop for Hole<'a, T, S> {
+    fn drop(&mut self) {
+        {
+            // Shift the elements after the slot one place to the left.
+            let rest = &mut self.queue.data.as_mut_slice()[self.index..];
+            let count = rest.len() - 1;
+            unsafe {
+                let p = rest.as_mut_ptr();
+                ptr::copy(p.offset(1), p, count);
+            }
+        }
+        // The last element was copied one slot to the left, so this is the second copy.
+        forget(self.queue.data.pop());
+
+        // The queue isn't full anymore, so its elements have to start at the beginning.
+        self.queue.insertion_index = self.index;
+        self.queue.make_contiguous();
+    }
+}
+
+impl<T, S: Storage<T>> CircularQueue<T, S> {
+    /// Constructs a new `CircularQueue<T, S>` on top of the provided storage.
+    ///
+    /// The capacity of the queue is the capacity of the storage. Elements already in the storage
+    /// are treated as going from the oldest to the newest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::{ArrayStorage, CircularQueue};
+    ///
+    /// let mut queue = CircularQueue::from_storage(ArrayStorage::<i32, 2>::new_array());
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.push(3);
+    ///
+    /// assert_eq!(queue, vec![2, 3]);
+    /// ```
+    #[inline]
+    pub fn from_storage(data: S) -> Self {
+        let capacity = data.capacity();
+        let len = data.as_slice().len();
+        Self {
+            data,
+            capacity,
+            insertion_index: if capacity == 0 { 0 } else { len % capacity },
+            overflow_policy: OverflowPolicy::Overwrite,
+            evictions: Evictions::new(),
         }
     }
 
@@ -116,7 +455,7 @@ impl<T> CircularQueue<T> {
     /// ```
     #[inline]
     pub fn len(&self) -> usize {
-        self.data.len()
+        self.data.as_slice().len()
     }
 
     /// Returns `true` if the queue contains no elements.
@@ -134,7 +473,7 @@ impl<T> CircularQueue<T> {
     /// ```
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.data.is_empty()
+        self.data.as_slice().is_empty()
     }
 
     /// Returns `true` if the queue is full.
@@ -176,6 +515,150 @@ impl<T> CircularQueue<T> {
         self.capacity
     }
 
+    /// Rotates the data so that the elements go from the oldest to the newest.
+    ///
+    /// The insertion index is left for the caller to update.
+    fn normalize(&mut self) {
+        let (a, b) = self.data.as_mut_slice().split_at_mut(self.insertion_index);
+        a.reverse();
+        b.reverse();
+        self.data.as_mut_slice().reverse();
+    }
+
+    /// Changes the capacity of the queue, assuming the memory has been reserved.
+    #[cfg(has_alloc)]
+    fn set_capacity_reserved(&mut self, capacity: usize) {
+        self.normalize();
+
+        let len = self.len();
+        if capacity < len {
+            let excess = len - capacity;
+            self.evictions.evict_all(&self.data.as_slice()[..excess]);
+
+            // Move the oldest elements to the end to truncate them away.
+            self.data.as_mut_slice().reverse();
+            self.data.truncate(capacity);
+            self.data.as_mut_slice().reverse();
+        }
+
+        self.capacity = capacity;
+        self.insertion_index = if capacity == 0 {
+            0
+        } else {
+            self.len() % capacity
+        };
+    }
+
+    /// Returns what `push()` does when the queue is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::{CircularQueue, OverflowPolicy};
+    ///
+    /// let queue: CircularQueue<i32> = CircularQueue::with_capacity(5);
+    /// assert_eq!(queue.overflow_policy(), OverflowPolicy::Overwrite);
+    /// ```
+    #[inline]
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.overflow_policy
+    }
+
+    /// Sets what `push()` does when the queue is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::{CircularQueue, OverflowPolicy};
+    ///
+    /// let mut queue = CircularQueue::with_capacity(2);
+    /// queue.set_overflow_policy(OverflowPolicy::Reject);
+    ///
+    /// queue.push(1);
+    /// queue.push(2);
+    /// assert_eq!(queue.push(3), Some(3));
+    /// assert_eq!(queue, vec![1, 2]);
+    /// ```
+    #[inline]
+    pub fn set_overflow_policy(&mut self, overflow_policy: OverflowPolicy) {
+        self.overflow_policy = overflow_policy;
+    }
+
+    /// Sets a callback invoked with every element evicted from the queue.
+    ///
+    /// An element is evicted when it's overwritten by `push()` or a similar method, when it's
+    /// removed by shrinking the capacity and when the queue is cleared. Elements moved out of the
+    /// queue, for example with `into_vec()`, aren't evicted. The callback runs after the element is
+    /// removed from the queue, right before it's returned or dropped.
+    ///
+    /// The callback isn't cloned together with the queue: the clone starts without a callback. It
+    /// has to be `Send` so that the queue stays `Send`, but it doesn't have to be `Sync`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let evicted = Arc::new(Mutex::new(Vec::new()));
+    ///
+    /// let mut queue = CircularQueue::with_capacity(2);
+    /// let evicted_in_callback = evicted.clone();
+    /// queue.set_on_evict(move |&x| evicted_in_callback.lock().unwrap().push(x));
+    ///
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.push(3);
+    /// queue.clear();
+    ///
+    /// assert_eq!(*evicted.lock().unwrap(), vec![1, 2, 3]);
+    /// ```
+    #[cfg(has_alloc)]
+    #[inline]
+    pub fn set_on_evict<F>(&mut self, callback: F)
+    where
+        F: FnMut(&T) + Send + 'static,
+    {
+        self.evictions.set_callback(Some(Box::new(callback)));
+    }
+
+    /// Removes the callback set with `set_on_evict()`.
+    #[cfg(has_alloc)]
+    #[inline]
+    pub fn remove_on_evict(&mut self) {
+        self.evictions.set_callback(None);
+    }
+
+    /// Returns the total number of elements pushed into the queue over its lifetime.
+    ///
+    /// Elements rejected because of `OverflowPolicy::Reject` aren't counted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(2);
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.push(3);
+    ///
+    /// assert_eq!(queue.total_pushed(), 3);
+    /// assert_eq!(queue.total_evicted(), 1);
+    /// ```
+    #[inline]
+    pub fn total_pushed(&self) -> u64 {
+        self.evictions.total_pushed()
+    }
+
+    /// Returns the total number of elements evicted from the queue over its lifetime.
+    ///
+    /// See `set_on_evict()` for what counts as an eviction.
+    #[inline]
+    pub fn total_evicted(&self) -> u64 {
+        self.evictions.total_evicted()
+    }
+
     /// Clears the queue.
     ///
     /// # Examples
@@ -193,7 +676,10 @@ impl<T> CircularQueue<T> {
     /// ```
     #[inline]
     pub fn clear(&mut self) {
-        self.data.clear();
+        let (newer, older) = self.data.as_slice().split_at(self.insertion_index);
+        self.evictions.evict_all(older.iter().chain(newer));
+
+        self.data.truncate(0);
         self.insertion_index = 0;
     }
 
@@ -201,7 +687,10 @@ impl<T> CircularQueue<T> {
     ///
     /// Once the capacity is reached, pushing new items will overwrite old ones.
     ///
-    /// In case an old value is overwritten, it will be returned.
+    /// In case an old value is overwritten, it will be returned. If the overflow policy is
+    /// `OverflowPolicy::Reject`, the queue is left unchanged instead and the new value is returned.
+    ///
+    /// Once the queue is full, pushing never allocates.
     ///
     /// # Examples
     ///
@@ -228,105 +717,387 @@ impl<T> CircularQueue<T> {
     pub fn push(&mut self, x: T) -> Popped<T> {
         let mut old = None;
 
+        if self.is_full() && self.overflow_policy == OverflowPolicy::Reject {
+            return Some(x);
+        }
+
         if self.capacity() == 0 {
+            self.evictions.pushed(1);
+            self.evictions.evict(&x);
             return old;
         }
 
         if !self.is_full() {
+            if self.data.as_slice().len() == self.data.capacity() {
+                // Lazy preallocation: grow the same way as Vec, but never beyond the capacity.
+                let additional = cmp::min(
+                    cmp::max(self.data.as_slice().len(), 4),
+                    self.capacity - self.len(),
+                );
+                self.data.reserve_exact(additional);
+            }
+
             self.data.push(x);
         } else {
-            old = Some(replace(&mut self.data[self.insertion_index], x));
+            old = Some(replace(
+                &mut self.data.as_mut_slice()[self.insertion_index],
+                x,
+            ));
         }
 
         self.insertion_index = (self.insertion_index + 1) % self.capacity();
 
+        self.evictions.pushed(1);
+        if let Some(ref old) = old {
+            self.evictions.evict(old);
+        }
+
         old
     }
 
-    /// Returns an iterator over the queue's contents.
+    /// Pushes a new element created by `f` into the queue.
     ///
-    /// The iterator goes from the most recently pushed items to the oldest ones.
+    /// If the queue is full, `f` is called with the element being overwritten, and the element it
+    /// returns is written to the same slot. This allows reusing the allocations of the old element,
+    /// such as the buffer of a `String` or a `Vec`. Otherwise, `f` is called with `None`.
+    ///
+    /// If the queue is full and the overflow policy is `OverflowPolicy::Reject`, `f` isn't called
+    /// and is returned in `Err`.
+    ///
+    /// The overwritten element counts as evicted, and the eviction callback is invoked with it
+    /// before it's passed to `f`. If `f` panics, the overwritten element is removed from the queue.
     ///
     /// # Examples
     ///
     /// ```
     /// use circular_queue::CircularQueue;
     ///
-    /// let mut queue = CircularQueue::with_capacity(3);
-    /// queue.push(1);
-    /// queue.push(2);
-    /// queue.push(3);
-    /// queue.push(4);
+    /// let mut lines: CircularQueue<String> = CircularQueue::with_capacity(2);
     ///
-    /// let mut iter = queue.iter();
+    /// for &line in &["first", "second", "third"] {
+    ///     let _ = lines.push_with(|old| {
+    ///         // Reuse the buffer of the overwritten line.
+    ///         let mut buffer = old.unwrap_or_default();
+    ///         buffer.clear();
+    ///         buffer.push_str(line);
+    ///         buffer
+    ///     });
+    /// }
     ///
-    /// assert_eq!(iter.next(), Some(&4));
-    /// assert_eq!(iter.next(), Some(&3));
-    /// assert_eq!(iter.next(), Some(&2));
+    /// assert_eq!(lines, vec!["second", "third"]);
     /// ```
     #[inline]
-    pub fn iter(&self) -> Iter<T> {
-        let (a, b) = self.data.split_at(self.insertion_index);
-        a.iter().rev().chain(b.iter().rev())
+    pub fn push_with<F>(&mut self, f: F) -> Result<(), F>
+    where
+        F: FnOnce(Option<T>) -> T,
+    {
+        if self.is_full() && self.overflow_policy == OverflowPolicy::Reject {
+            return Err(f);
+        }
+
+        if self.capacity() == 0 || !self.is_full() {
+            self.push(f(None));
+            return Ok(());
+        }
+
+        let index = self.insertion_index;
+        self.evictions.evict(&self.data.as_slice()[index]);
+
+        {
+            let hole = Hole { queue: self, index };
+            // The slot is left logically uninitialized until the new element is written to it.
+            // `hole` removes it from the queue if `f` panics.
+            let old = unsafe { ptr::read(&hole.queue.data.as_slice()[index]) };
+            let new = f(Some(old));
+            unsafe { ptr::write(&mut hole.queue.data.as_mut_slice()[index], new) };
+            forget(hole);
+        }
+
+        self.insertion_index = (index + 1) % self.capacity();
+        self.evictions.pushed(1);
+        Ok(())
     }
 
-    /// Returns a mutable iterator over the queue's contents.
+    /// Updates the most recently pushed element in place or pushes a new one.
     ///
-    /// The iterator goes from the most recently pushed items to the oldest ones.
+    /// `update` is called with the newest element and returns whether it was updated. If it
+    /// wasn't, or if the queue is empty, the element created by `new` is pushed as if by
+    /// `push_with()`, reusing the overwritten element if the queue is full. If the queue rejects
+    /// the new element, `new` is returned in `Err`.
     ///
     /// # Examples
     ///
     /// ```
     /// use circular_queue::CircularQueue;
     ///
-    /// let mut queue = CircularQueue::with_capacity(3);
-    /// queue.push(1);
-    /// queue.push(2);
-    /// queue.push(3);
-    /// queue.push(4);
-    ///
-    /// let mut iter = queue.iter_mut();
+    /// // Counts repeated messages instead of storing each of them.
+    /// let mut queue = CircularQueue::with_capacity(4);
+    /// for &message in &["a", "a", "b", "a", "a", "a"] {
+    ///     let _ = queue.push_or_update(
+    ///         |last: &mut (&str, usize)| {
+    ///             if last.0 == message {
+    ///                 last.1 += 1;
+    ///                 true
+    ///             } else {
+    ///                 false
+    ///             }
+    ///         },
+    ///         |_| (message, 1),
+    ///     );
+    /// }
     ///
-    /// assert_eq!(iter.next(), Some(&mut 4));
-    /// assert_eq!(iter.next(), Some(&mut 3));
-    /// assert_eq!(iter.next(), Some(&mut 2));
+    /// assert_eq!(queue, vec![("a", 2), ("b", 1), ("a", 3)]);
     /// ```
     #[inline]
-    pub fn iter_mut(&mut self) -> IterMut<T> {
-        let (a, b) = self.data.split_at_mut(self.insertion_index);
-        a.iter_mut().rev().chain(b.iter_mut().rev())
+    pub fn push_or_update<F, G>(&mut self, update: F, new: G) -> Result<(), G>
+    where
+        F: FnOnce(&mut T) -> bool,
+        G: FnOnce(Option<T>) -> T,
+    {
+        if let Some(newest) = self.newest_mut() {
+            if update(newest) {
+                return Ok(());
+            }
+        }
+
+        self.push_with(new)
     }
 
-    /// Returns an ascending iterator over the queue's contents.
+    /// Returns a mutable reference to the slot that the next pushed element will overwrite, so that
+    /// its contents can be reused for the new element.
     ///
-    /// The iterator goes from the least recently pushed items to the newest ones.
+    /// The slot is available only when the queue is full and the overflow policy is
+    /// `OverflowPolicy::Overwrite`, and it holds the oldest element. Writing to the slot changes
+    /// the oldest element in place. After updating it, call `commit()` to make it the newest
+    /// element. Until then, the order of the elements is left unchanged.
     ///
     /// # Examples
     ///
     /// ```
     /// use circular_queue::CircularQueue;
     ///
-    /// let mut queue = CircularQueue::with_capacity(3);
-    /// queue.push(1);
-    /// queue.push(2);
-    /// queue.push(3);
-    /// queue.push(4);
+    /// let mut packets: CircularQueue<Vec<u8>> = CircularQueue::with_capacity(2);
     ///
-    /// let mut iter = queue.asc_iter();
+    /// for payload in &[&b"first"[..], b"second", b"third"] {
+    ///     match packets.next_slot_mut() {
+    ///         Some(buffer) => {
+    ///             // Reuse the oldest buffer instead of allocating a new one.
+    ///             buffer.clear();
+    ///             buffer.extend_from_slice(payload);
+    ///         }
+    ///         None => {
+    ///             packets.push(payload.to_vec());
+    ///             continue;
+    ///         }
+    ///     }
+    ///     packets.commit();
+    /// }
     ///
-    /// assert_eq!(iter.next(), Some(&2));
-    /// assert_eq!(iter.next(), Some(&3));
-    /// assert_eq!(iter.next(), Some(&4));
+    /// assert_eq!(packets, vec![b"second".to_vec(), b"third".to_vec()]);
     /// ```
     #[inline]
-    pub fn asc_iter(&self) -> AscIter<T> {
-        let (a, b) = self.data.split_at(self.insertion_index);
-        b.iter().chain(a.iter())
+    pub fn next_slot_mut(&mut self) -> Option<&mut T> {
+        if self.is_empty() || !self.is_full() || self.overflow_policy == OverflowPolicy::Reject {
+            return None;
+        }
+
+        Some(&mut self.data.as_mut_slice()[self.insertion_index])
     }
 
-    /// Returns a mutable ascending iterator over the queue's contents.
+    /// Makes the slot returned by `next_slot_mut()` the most recently pushed element.
     ///
-    /// The iterator goes from the least recently pushed items to the newest ones.
+    /// This counts as pushing an element and evicting the oldest one, but the eviction callback
+    /// isn't invoked since the evicted element was reused.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `next_slot_mut()` would return `None`.
+    #[inline]
+    pub fn commit(&mut self) {
+        assert!(self.next_slot_mut().is_some(), "there is no slot to commit");
+
+        self.insertion_index = (self.insertion_index + 1) % self.capacity();
+        self.evictions.pushed(1);
+        self.evictions.evicted(1);
+    }
+
+    /// Returns the index in the storage of the most recently pushed element.
+    #[inline]
+    fn newest_index(&self) -> Option<usize> {
+        if self.is_empty() {
+            None
+        } else if self.insertion_index == 0 {
+            Some(self.len() - 1)
+        } else {
+            Some(self.insertion_index - 1)
+        }
+    }
+
+    /// Returns the index in the storage of the oldest element.
+    #[inline]
+    fn oldest_index(&self) -> Option<usize> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.insertion_index % self.len())
+        }
+    }
+
+    /// Returns the most recently pushed element, or `None` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(2);
+    /// assert_eq!(queue.newest(), None);
+    ///
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.push(3);
+    /// assert_eq!(queue.newest(), Some(&3));
+    /// ```
+    #[inline]
+    pub fn newest(&self) -> Option<&T> {
+        match self.newest_index() {
+            Some(index) => Some(&self.data.as_slice()[index]),
+            None => None,
+        }
+    }
+
+    /// Returns a mutable reference to the most recently pushed element, or `None` if the queue is
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(2);
+    /// queue.push(1);
+    /// queue.push(2);
+    ///
+    /// *queue.newest_mut().unwrap() = 5;
+    /// assert_eq!(queue, vec![1, 5]);
+    /// ```
+    #[inline]
+    pub fn newest_mut(&mut self) -> Option<&mut T> {
+        match self.newest_index() {
+            Some(index) => Some(&mut self.data.as_mut_slice()[index]),
+            None => None,
+        }
+    }
+
+    /// Returns the oldest element, or `None` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(2);
+    /// assert_eq!(queue.oldest(), None);
+    ///
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.push(3);
+    /// assert_eq!(queue.oldest(), Some(&2));
+    /// ```
+    #[inline]
+    pub fn oldest(&self) -> Option<&T> {
+        match self.oldest_index() {
+            Some(index) => Some(&self.data.as_slice()[index]),
+            None => None,
+        }
+    }
+
+    /// Returns a mutable reference to the oldest element, or `None` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(2);
+    /// queue.push(1);
+    /// queue.push(2);
+    ///
+    /// *queue.oldest_mut().unwrap() = 5;
+    /// assert_eq!(queue, vec![5, 2]);
+    /// ```
+    #[inline]
+    pub fn oldest_mut(&mut self) -> Option<&mut T> {
+        match self.oldest_index() {
+            Some(index) => Some(&mut self.data.as_mut_slice()[index]),
+            None => None,
+        }
+    }
+
+    /// Returns the index in the storage of the element at `index`, counting from the oldest one.
+    #[inline]
+    fn storage_index(&self, index: usize) -> Option<usize> {
+        if index < self.len() {
+            Some((self.insertion_index + index) % self.len())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the element at `index`, counting from the oldest one, or `None` if it's out of
+    /// bounds.
+    ///
+    /// Index 0 is the oldest element and index `len() - 1` is the newest one, like the order of
+    /// `asc_iter()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(3);
+    /// queue.extend(vec![1, 2, 3, 4]);
+    ///
+    /// assert_eq!(queue.get(0), Some(&2));
+    /// assert_eq!(queue.get(2), Some(&4));
+    /// assert_eq!(queue.get(3), None);
+    /// ```
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        match self.storage_index(index) {
+            Some(index) => Some(&self.data.as_slice()[index]),
+            None => None,
+        }
+    }
+
+    /// Returns a mutable reference to the element at `index`, counting from the oldest one, or
+    /// `None` if it's out of bounds.
+    ///
+    /// Index 0 is the oldest element and index `len() - 1` is the newest one, like the order of
+    /// `asc_iter()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(3);
+    /// queue.extend(vec![1, 2, 3, 4]);
+    ///
+    /// *queue.get_mut(1).unwrap() = 0;
+    /// assert_eq!(queue, [2, 0, 4]);
+    /// ```
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        match self.storage_index(index) {
+            Some(index) => Some(&mut self.data.as_mut_slice()[index]),
+            None => None,
+        }
+    }
+
+    /// Returns an iterator over the queue's contents.
+    ///
+    /// The iterator goes from the most recently pushed items to the oldest ones.
     ///
     /// # Examples
     ///
@@ -339,20 +1110,21 @@ impl<T> CircularQueue<T> {
     /// queue.push(3);
     /// queue.push(4);
     ///
-    /// let mut iter = queue.asc_iter_mut();
+    /// let mut iter = queue.iter();
     ///
-    /// assert_eq!(iter.next(), Some(&mut 2));
-    /// assert_eq!(iter.next(), Some(&mut 3));
-    /// assert_eq!(iter.next(), Some(&mut 4));
+    /// assert_eq!(iter.next(), Some(&4));
+    /// assert_eq!(iter.next(), Some(&3));
+    /// assert_eq!(iter.next(), Some(&2));
     /// ```
     #[inline]
-    pub fn asc_iter_mut(&mut self) -> AscIterMut<T> {
-        let (a, b) = self.data.split_at_mut(self.insertion_index);
-        b.iter_mut().chain(a.iter_mut())
+    pub fn iter(&self) -> Iter<T> {
+        let (a, b) = self.data.as_slice().split_at(self.insertion_index);
+        a.iter().rev().chain(b.iter().rev())
     }
 
-    /// Converts the queue into a `Vec<T>` going from the most recently pushed items to the oldest
-    /// ones.
+    /// Returns a mutable iterator over the queue's contents.
+    ///
+    /// The iterator goes from the most recently pushed items to the oldest ones.
     ///
     /// # Examples
     ///
@@ -365,335 +1137,1672 @@ impl<T> CircularQueue<T> {
     /// queue.push(3);
     /// queue.push(4);
     ///
-    /// let v = queue.into_vec();
+    /// let mut iter = queue.iter_mut();
     ///
-    /// assert_eq!(v, vec![4, 3, 2]);
+    /// assert_eq!(iter.next(), Some(&mut 4));
+    /// assert_eq!(iter.next(), Some(&mut 3));
+    /// assert_eq!(iter.next(), Some(&mut 2));
     /// ```
     #[inline]
-    pub fn into_vec(mut self) -> Vec<T> {
-        self.data[self.insertion_index..].reverse(); // Reverse the upper part.
-        self.data[..self.insertion_index].reverse(); // Reverse the lower part.
-        self.data
+    pub fn iter_mut(&mut self) -> IterMut<T> {
+        let (a, b) = self.data.as_mut_slice().split_at_mut(self.insertion_index);
+        a.iter_mut().rev().chain(b.iter_mut().rev())
     }
-}
 
-impl<T: PartialEq> PartialEq for CircularQueue<T> {
+    /// Returns an ascending iterator over the queue's contents.
+    ///
+    /// The iterator goes from the least recently pushed items to the newest ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(3);
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.push(3);
+    /// queue.push(4);
+    ///
+    /// let mut iter = queue.asc_iter();
+    ///
+    /// assert_eq!(iter.next(), Some(&2));
+    /// assert_eq!(iter.next(), Some(&3));
+    /// assert_eq!(iter.next(), Some(&4));
+    /// ```
     #[inline]
-    fn eq(&self, other: &CircularQueue<T>) -> bool {
-        self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    pub fn asc_iter(&self) -> AscIter<T> {
+        let (a, b) = self.data.as_slice().split_at(self.insertion_index);
+        b.iter().chain(a.iter())
     }
-}
-
-impl<T: Eq> Eq for CircularQueue<T> {}
 
-#[cfg(has_relaxed_orphan_rule)]
-impl<T> From<CircularQueue<T>> for Vec<T> {
+    /// Returns a mutable ascending iterator over the queue's contents.
+    ///
+    /// The iterator goes from the least recently pushed items to the newest ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(3);
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.push(3);
+    /// queue.push(4);
+    ///
+    /// let mut iter = queue.asc_iter_mut();
+    ///
+    /// assert_eq!(iter.next(), Some(&mut 2));
+    /// assert_eq!(iter.next(), Some(&mut 3));
+    /// assert_eq!(iter.next(), Some(&mut 4));
+    /// ```
     #[inline]
-    fn from(queue: CircularQueue<T>) -> Self {
-        queue.into_vec()
+    pub fn asc_iter_mut(&mut self) -> AscIterMut<T> {
+        let (a, b) = self.data.as_mut_slice().split_at_mut(self.insertion_index);
+        b.iter_mut().chain(a.iter_mut())
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    #[cfg(has_extern_crate_alloc)]
-    use alloc::vec;
 
-    #[test]
-    fn zero_capacity() {
-        let mut q = CircularQueue::<i32>::with_capacity(0);
-        assert_eq!(q.len(), 0);
-        assert_eq!(q.capacity(), 0);
-        assert!(q.is_empty());
+    /// Returns `true` if the queues are equal and have the same capacity.
+    ///
+    /// Unlike `==`, which only compares the elements, this also compares the capacities.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut q1 = CircularQueue::with_capacity(3);
+    /// q1.push(1);
+    ///
+    /// let mut q2 = CircularQueue::with_capacity(4);
+    /// q2.push(1);
+    ///
+    /// assert!(q1 == q2);
+    /// assert!(!q1.eq_capacity(&q2));
+    /// ```
+    #[inline]
+    pub fn eq_capacity<S2: Storage<T>>(&self, other: &CircularQueue<T, S2>) -> bool
+    where
+        T: PartialEq,
+    {
+        self.capacity == other.capacity && self == other
+    }
 
-        q.push(3);
-        q.push(4);
-        q.push(5);
+    /// Compares the queue with a slice going from the oldest items to the newest ones.
+    #[inline]
+    fn eq_asc_slice<U>(&self, other: &[U]) -> bool
+    where
+        T: PartialEq<U>,
+    {
+        if self.len() != other.len() {
+            return false;
+        }
 
-        assert_eq!(q.len(), 0);
-        assert_eq!(q.capacity(), 0);
-        assert!(q.is_empty());
+        let (a, b) = self.data.as_slice().split_at(self.insertion_index);
+        let (other_b, other_a) = other.split_at(b.len());
+        b == other_b && a == other_a
+    }
+}
 
-        assert_eq!(q.iter().count(), 0);
-        assert_eq!(q.asc_iter().count(), 0);
+impl<T: Copy, S: Storage<T>> CircularQueue<T, S> {
+    /// Copies the queue's contents into `dst`, going from the oldest items to the newest ones.
+    ///
+    /// This is done with at most two `memcpy`s.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `dst` is different from the length of the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(3);
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.push(3);
+    /// queue.push(4);
+    ///
+    /// let mut buf = vec![0; 3];
+    /// queue.copy_to_slice_asc(&mut buf);
+    /// assert_eq!(buf, vec![2, 3, 4]);
+    /// ```
+    #[inline]
+    pub fn copy_to_slice_asc(&self, dst: &mut [T]) {
+        assert_eq!(
+            dst.len(),
+            self.len(),
+            "destination length must be equal to the queue length"
+        );
+        self.copy_newest_n(dst);
+    }
 
-        q.clear();
+    /// Copies the `dst.len()` most recently pushed items into `dst`, going from the oldest of them
+    /// to the newest one.
+    ///
+    /// This is done with at most two `memcpy`s.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` is longer than the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(3);
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.push(3);
+    /// queue.push(4);
+    ///
+    /// let mut buf = vec![0; 2];
+    /// queue.copy_newest_n(&mut buf);
+    /// assert_eq!(buf, vec![3, 4]);
+    /// ```
+    #[inline]
+    pub fn copy_newest_n(&self, dst: &mut [T]) {
+        let n = dst.len();
+        assert!(
+            n <= self.len(),
+            "destination length must not exceed the queue length"
+        );
+
+        let (newer, older) = self.data.as_slice().split_at(self.insertion_index);
+        if n <= newer.len() {
+            dst.copy_from_slice(&newer[newer.len() - n..]);
+        } else {
+            let (dst_older, dst_newer) = dst.split_at_mut(n - newer.len());
+            dst_older.copy_from_slice(&older[older.len() - dst_older.len()..]);
+            dst_newer.copy_from_slice(newer);
+        }
     }
 
-    #[test]
-    fn empty_queue() {
-        let q = CircularQueue::<i32>::with_capacity(5);
+    /// Pushes the items of `src` into the queue in order, as if by calling `push()` for each of
+    /// them, and returns the number of items pushed.
+    ///
+    /// This is done with at most two `memcpy`s. If the overflow policy is `OverflowPolicy::Reject`,
+    /// the items that don't fit are left out and not counted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::{CircularQueue, OverflowPolicy};
+    ///
+    /// let mut queue = CircularQueue::with_capacity(4);
+    /// queue.push(1);
+    ///
+    /// assert_eq!(queue.write_from_slice(&[2, 3, 4, 5, 6]), 5);
+    /// assert_eq!(queue, vec![3, 4, 5, 6]);
+    ///
+    /// queue.clear();
+    /// queue.set_overflow_policy(OverflowPolicy::Reject);
+    ///
+    /// assert_eq!(queue.write_from_slice(&[1, 2, 3, 4, 5, 6]), 4);
+    /// assert_eq!(queue, vec![1, 2, 3, 4]);
+    /// ```
+    pub fn write_from_slice(&mut self, src: &[T]) -> usize {
+        let capacity = self.capacity();
+        let len = self.len();
+
+        let mut src = src;
+        if self.overflow_policy == OverflowPolicy::Reject {
+            src = &src[..cmp::min(src.len(), capacity - len)];
+        }
+        let pushed = src.len();
+
+        let evicted = (len + src.len()).saturating_sub(capacity);
+        let evicted_from_queue = cmp::min(evicted, len);
+        {
+            let (newer, older) = self.data.as_slice().split_at(self.insertion_index);
+            self.evictions
+                .evict_all(older.iter().chain(newer).take(evicted_from_queue));
+        }
+        self.evictions
+            .evict_all(&src[..evicted - evicted_from_queue]);
+        self.evictions.pushed(pushed);
+
+        if capacity == 0 {
+            return pushed;
+        }
+
+        // Items that would be overwritten by later items of `src` don't need to be written.
+        if src.len() > capacity {
+            src = &src[src.len() - capacity..];
+        }
+
+        // While the queue isn't full, the insertion index is at the end of the storage.
+        let fill = cmp::min(src.len(), capacity - len);
+        if fill > 0 {
+            let spare = self.data.capacity() - len;
+            if spare < fill {
+                self.data.reserve_exact(fill - spare);
+            }
+            self.data.extend_from_slice(&src[..fill]);
+            self.insertion_index = (len + fill) % capacity;
+            src = &src[fill..];
+        }
+
+        if !src.is_empty() {
+            let index = self.insertion_index;
+            let (head, tail) = src.split_at(cmp::min(src.len(), capacity - index));
+            let data = self.data.as_mut_slice();
+            data[index..index + head.len()].copy_from_slice(head);
+            data[..tail.len()].copy_from_slice(tail);
+            self.insertion_index = (index + src.len()) % capacity;
+        }
+
+        pushed
+    }
+}
+
+#[cfg(has_alloc)]
+impl<T> CircularQueue<T> {
+    /// Changes the capacity of the queue.
+    ///
+    /// If the new capacity is smaller than the current length, the oldest elements are dropped.
+    /// If it is larger, memory for the whole new capacity is reserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(3);
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.push(3);
+    /// queue.push(4);
+    ///
+    /// queue.set_capacity(2);
+    /// assert_eq!(queue, vec![3, 4]);
+    ///
+    /// queue.set_capacity(4);
+    /// queue.push(5);
+    /// assert_eq!(queue, vec![3, 4, 5]);
+    /// ```
+    pub fn set_capacity(&mut self, capacity: usize) {
+        if capacity > self.len() {
+            let additional = capacity - self.len();
+            self.data.reserve_exact(additional);
+        }
+
+        self.set_capacity_reserved(capacity);
+    }
+
+    /// Changes the capacity of the queue, returning an error if the allocation fails.
+    ///
+    /// If the new capacity is smaller than the current length, the oldest elements are dropped.
+    /// If it is larger, memory for the whole new capacity is reserved. In case of an error, the
+    /// queue is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(3);
+    /// queue.push(1);
+    ///
+    /// assert!(queue.try_set_capacity(5).is_ok());
+    /// assert_eq!(queue.capacity(), 5);
+    ///
+    /// assert!(queue.try_set_capacity(usize::MAX).is_err());
+    /// assert_eq!(queue.capacity(), 5);
+    /// ```
+    #[cfg(has_try_reserve)]
+    pub fn try_set_capacity(&mut self, capacity: usize) -> Result<(), TryReserveError> {
+        if capacity > self.len() {
+            let additional = capacity - self.len();
+            self.data.try_reserve_exact(additional)?;
+        }
+
+        self.set_capacity_reserved(capacity);
+        Ok(())
+    }
+
+    /// Converts the queue into a `Vec<T>` going from the most recently pushed items to the oldest
+    /// ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(3);
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.push(3);
+    /// queue.push(4);
+    ///
+    /// let v = queue.into_vec();
+    ///
+    /// assert_eq!(v, vec![4, 3, 2]);
+    /// ```
+    #[inline]
+    pub fn into_vec(mut self) -> Vec<T> {
+        self.data[self.insertion_index..].reverse(); // Reverse the upper part.
+        self.data[..self.insertion_index].reverse(); // Reverse the lower part.
+        self.data
+    }
+
+    /// Converts the queue into a `Vec<T>` going from the oldest items to the most recently pushed
+    /// ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(3);
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.push(3);
+    /// queue.push(4);
+    ///
+    /// let v = queue.into_vec_asc();
+    ///
+    /// assert_eq!(v, vec![2, 3, 4]);
+    /// ```
+    #[inline]
+    pub fn into_vec_asc(mut self) -> Vec<T> {
+        self.normalize();
+        self.data
+    }
+
+    /// Converts the queue into a `Box<[T]>` going from the most recently pushed items to the
+    /// oldest ones.
+    ///
+    /// Like `Vec::into_boxed_slice()`, this reallocates if the queue isn't full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(3);
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.push(3);
+    /// queue.push(4);
+    ///
+    /// let b = queue.into_boxed_slice();
+    ///
+    /// assert_eq!(&*b, &[4, 3, 2]);
+    /// ```
+    #[inline]
+    pub fn into_boxed_slice(self) -> Box<[T]> {
+        self.into_vec().into_boxed_slice()
+    }
+}
+
+impl<T, S, S2> PartialEq<CircularQueue<T, S2>> for CircularQueue<T, S>
+where
+    T: PartialEq,
+    S: Storage<T>,
+    S2: Storage<T>,
+{
+    #[inline]
+    fn eq(&self, other: &CircularQueue<T, S2>) -> bool {
+        self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    }
+}
+
+impl<T: Eq, S: Storage<T>> Eq for CircularQueue<T, S> {}
+
+impl<T, S, U> PartialEq<[U]> for CircularQueue<T, S>
+where
+    T: PartialEq<U>,
+    S: Storage<T>,
+{
+    #[inline]
+    fn eq(&self, other: &[U]) -> bool {
+        self.eq_asc_slice(other)
+    }
+}
+
+impl<'a, T, S, U> PartialEq<&'a [U]> for CircularQueue<T, S>
+where
+    T: PartialEq<U>,
+    S: Storage<T>,
+{
+    #[inline]
+    fn eq(&self, other: &&'a [U]) -> bool {
+        self.eq_asc_slice(other)
+    }
+}
+
+#[cfg(has_alloc)]
+impl<T, S, U> PartialEq<Vec<U>> for CircularQueue<T, S>
+where
+    T: PartialEq<U>,
+    S: Storage<T>,
+{
+    #[inline]
+    fn eq(&self, other: &Vec<U>) -> bool {
+        self.eq_asc_slice(other)
+    }
+}
+
+#[cfg(has_alloc)]
+impl<T, S, U> PartialEq<VecDeque<U>> for CircularQueue<T, S>
+where
+    T: PartialEq<U>,
+    S: Storage<T>,
+{
+    #[inline]
+    fn eq(&self, other: &VecDeque<U>) -> bool {
+        self.len() == other.len() && self.asc_iter().zip(other.iter()).all(|(a, b)| a == b)
+    }
+}
+
+impl<T, S, S2> PartialOrd<CircularQueue<T, S2>> for CircularQueue<T, S>
+where
+    T: PartialOrd,
+    S: Storage<T>,
+    S2: Storage<T>,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &CircularQueue<T, S2>) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord, S: Storage<T>> Ord for CircularQueue<T, S> {
+    #[inline]
+    fn cmp(&self, other: &CircularQueue<T, S>) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash, S: Storage<T>> Hash for CircularQueue<T, S> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for x in self.iter() {
+            x.hash(state);
+        }
+    }
+}
+
+impl<T: fmt::Debug, S: Storage<T>> fmt::Debug for CircularQueue<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CircularQueue")
+            .field("data", &self.data.as_slice())
+            .field("capacity", &self.capacity)
+            .field("insertion_index", &self.insertion_index)
+            .field("overflow_policy", &self.overflow_policy)
+            .finish()
+    }
+}
+
+#[cfg(all(has_alloc, has_relaxed_orphan_rule))]
+impl<T> From<CircularQueue<T>> for Vec<T> {
+    #[inline]
+    fn from(queue: CircularQueue<T>) -> Self {
+        queue.into_vec()
+    }
+}
+
+#[cfg(all(has_alloc, has_relaxed_orphan_rule))]
+impl<T> From<CircularQueue<T>> for VecDeque<T> {
+    /// Converts the queue into a `VecDeque<T>` going from the oldest items to the most recently
+    /// pushed ones, reusing the allocation.
+    #[inline]
+    fn from(queue: CircularQueue<T>) -> Self {
+        VecDeque::from(queue.into_vec_asc())
+    }
+}
+
+#[cfg(has_alloc)]
+impl<T> From<Vec<T>> for CircularQueue<T> {
+    /// Converts a `Vec<T>` going from the oldest items to the newest ones into a full queue with
+    /// capacity equal to the length of the `Vec`, reusing the allocation.
+    ///
+    /// This is the supported way to convert a `Vec<T>`: it can't fail, since any length, including
+    /// zero, makes a valid capacity. To choose a different capacity, call `set_capacity()` on the
+    /// result or use `from_iter_with_capacity()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::from(vec![1, 2, 3]);
+    /// assert_eq!(queue.capacity(), 3);
+    /// assert!(queue.is_full());
+    ///
+    /// assert_eq!(queue.push(4), Some(1));
+    /// ```
+    #[inline]
+    fn from(data: Vec<T>) -> Self {
+        Self {
+            capacity: data.len(),
+            data,
+            insertion_index: 0,
+            overflow_policy: OverflowPolicy::Overwrite,
+            evictions: Evictions::new(),
+        }
+    }
+}
+
+#[cfg(has_alloc)]
+impl<T> Default for CircularQueue<T> {
+    /// Creates an empty `CircularQueue<T>` with zero capacity.
+    #[inline]
+    fn default() -> Self {
+        Self::with_capacity(0)
+    }
+}
+
+impl<T, S: Storage<T>> Extend<T> for CircularQueue<T, S> {
+    /// Pushes the items of `iter` into the queue in order.
+    ///
+    /// Like with `push()`, every item counts towards `total_pushed()`, and every element it
+    /// overwrites is evicted.
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a, S: Storage<T>> Extend<&'a T> for CircularQueue<T, S> {
+    /// Pushes the items of `iter` into the queue in order.
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        for &x in iter {
+            self.push(x);
+        }
+    }
+}
+
+#[cfg(has_alloc)]
+impl<T> FromIterator<T> for CircularQueue<T> {
+    /// Creates a full queue with capacity equal to the number of items in `iter`, going from the
+    /// oldest items to the newest ones.
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+#[cfg(has_alloc)]
+impl<T> From<VecDeque<T>> for CircularQueue<T> {
+    /// Converts a `VecDeque<T>` going from the oldest items to the newest ones into a full queue
+    /// with capacity equal to the length of the `VecDeque`, reusing the allocation.
+    #[inline]
+    fn from(deque: VecDeque<T>) -> Self {
+        Vec::from(deque).into()
+    }
+}
+
+#[cfg(all(test, has_alloc))]
+mod tests {
+    use super::*;
+    #[cfg(has_extern_crate_alloc)]
+    use alloc::vec;
+
+    #[test]
+    fn zero_capacity() {
+        let mut q = CircularQueue::<i32>::with_capacity(0);
+        assert_eq!(q.len(), 0);
+        assert_eq!(q.capacity(), 0);
+        assert!(q.is_empty());
+
+        q.push(3);
+        q.push(4);
+        q.push(5);
+
+        assert_eq!(q.len(), 0);
+        assert_eq!(q.capacity(), 0);
+        assert!(q.is_empty());
+
+        assert_eq!(q.iter().count(), 0);
+        assert_eq!(q.asc_iter().count(), 0);
+
+        q.clear();
+    }
+
+    #[test]
+    fn empty_queue() {
+        let q = CircularQueue::<i32>::with_capacity(5);
+
+        assert!(q.is_empty());
+        assert_eq!(q.iter().next(), None);
+    }
+
+    #[test]
+    fn partially_full_queue() {
+        let mut q = CircularQueue::with_capacity(5);
+        q.push(1);
+        q.push(2);
+        q.push(3);
+
+        assert!(!q.is_empty());
+        assert_eq!(q.len(), 3);
+
+        let res: Vec<_> = q.iter().map(|&x| x).collect();
+        assert_eq!(res, [3, 2, 1]);
+    }
+
+    #[test]
+    fn full_queue() {
+        let mut q = CircularQueue::with_capacity(5);
+        q.push(1);
+        q.push(2);
+        q.push(3);
+        q.push(4);
+        q.push(5);
+
+        assert_eq!(q.len(), 5);
+
+        let res: Vec<_> = q.iter().map(|&x| x).collect();
+        assert_eq!(res, [5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn over_full_queue() {
+        let mut q = CircularQueue::with_capacity(5);
+        q.push(1);
+        q.push(2);
+        q.push(3);
+        q.push(4);
+        q.push(5);
+        q.push(6);
+        q.push(7);
+
+        assert_eq!(q.len(), 5);
+
+        let res: Vec<_> = q.iter().map(|&x| x).collect();
+        assert_eq!(res, [7, 6, 5, 4, 3]);
+    }
+
+    #[test]
+    fn clear() {
+        let mut q = CircularQueue::with_capacity(5);
+        q.push(1);
+        q.push(2);
+        q.push(3);
+        q.push(4);
+        q.push(5);
+        q.push(6);
+        q.push(7);
+
+        q.clear();
+
+        assert_eq!(q.len(), 0);
+        assert_eq!(q.iter().next(), None);
+
+        q.push(1);
+        q.push(2);
+        q.push(3);
+
+        assert_eq!(q.len(), 3);
+
+        let res: Vec<_> = q.iter().map(|&x| x).collect();
+        assert_eq!(res, [3, 2, 1]);
+    }
+
+    #[test]
+    fn mutable_iterator() {
+        let mut q = CircularQueue::with_capacity(5);
+        q.push(1);
+        q.push(2);
+        q.push(3);
+        q.push(4);
+        q.push(5);
+        q.push(6);
+        q.push(7);
+
+        for x in q.iter_mut() {
+            *x *= 2;
+        }
+
+        let res: Vec<_> = q.iter().map(|&x| x).collect();
+        assert_eq!(res, [14, 12, 10, 8, 6]);
+    }
+
+    #[test]
+    fn zero_sized() {
+        let mut q = CircularQueue::with_capacity(3);
+        assert_eq!(q.capacity(), 3);
+
+        q.push(());
+        q.push(());
+        q.push(());
+        q.push(());
+
+        assert_eq!(q.len(), 3);
+
+        let mut iter = q.iter();
+        assert_eq!(iter.next(), Some(&()));
+        assert_eq!(iter.next(), Some(&()));
+        assert_eq!(iter.next(), Some(&()));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn empty_queue_eq() {
+        let q1 = CircularQueue::<i32>::with_capacity(5);
+        let q2 = CircularQueue::<i32>::with_capacity(5);
+        assert_eq!(q1, q2);
+
+        let q3 = CircularQueue::<i32>::with_capacity(6);
+        assert_eq!(q1, q3); // Capacity doesn't matter as long as the same elements are yielded.
+    }
+
+    #[test]
+    fn partially_full_queue_eq() {
+        let mut q1 = CircularQueue::with_capacity(5);
+        q1.push(1);
+        q1.push(2);
+        q1.push(3);
+
+        let mut q2 = CircularQueue::with_capacity(5);
+        q2.push(1);
+        q2.push(2);
+        assert_ne!(q1, q2);
+
+        q2.push(3);
+        assert_eq!(q1, q2);
+
+        q2.push(4);
+        assert_ne!(q1, q2);
+    }
+
+    #[test]
+    fn full_queue_eq() {
+        let mut q1 = CircularQueue::with_capacity(5);
+        q1.push(1);
+        q1.push(2);
+        q1.push(3);
+        q1.push(4);
+        q1.push(5);
+
+        let mut q2 = CircularQueue::with_capacity(5);
+        q2.push(1);
+        q2.push(2);
+        q2.push(3);
+        q2.push(4);
+        q2.push(5);
+
+        assert_eq!(q1, q2);
+    }
+
+    #[test]
+    fn over_full_queue_eq() {
+        let mut q1 = CircularQueue::with_capacity(5);
+        q1.push(1);
+        q1.push(2);
+        q1.push(3);
+        q1.push(4);
+        q1.push(5);
+        q1.push(6);
+        q1.push(7);
+
+        let mut q2 = CircularQueue::with_capacity(5);
+        q2.push(1);
+        q2.push(2);
+        q2.push(3);
+        q2.push(4);
+        q2.push(5);
+        q2.push(6);
+        assert_ne!(q1, q2);
+
+        q2.push(7);
+        assert_eq!(q1, q2);
+
+        q2.push(8);
+        assert_ne!(q1, q2);
+
+        q2.push(3);
+        q2.push(4);
+        q2.push(5);
+        q2.push(6);
+        q2.push(7);
+        assert_eq!(q1, q2);
+    }
+
+    #[test]
+    fn clear_eq() {
+        let mut q1 = CircularQueue::with_capacity(5);
+        q1.push(1);
+        q1.push(2);
+        q1.push(3);
+        q1.push(4);
+        q1.push(5);
+        q1.push(6);
+        q1.push(7);
+        q1.clear();
+
+        let mut q2 = CircularQueue::with_capacity(5);
+        assert_eq!(q1, q2);
+
+        q2.push(1);
+        q2.clear();
+        assert_eq!(q1, q2);
+    }
+
+    #[test]
+    fn zero_sized_eq() {
+        let mut q1 = CircularQueue::with_capacity(3);
+        q1.push(());
+        q1.push(());
+        q1.push(());
+        q1.push(());
+
+        let mut q2 = CircularQueue::with_capacity(3);
+        q2.push(());
+        q2.push(());
+        assert_ne!(q1, q2);
+
+        q2.push(());
+        assert_eq!(q1, q2);
+
+        q2.push(());
+        assert_eq!(q1, q2);
+
+        q2.push(());
+        assert_eq!(q1, q2);
+    }
+
+    /// Returns queues of every capacity up to 3 with every sequence of up to 4 pushes of 0, 1 or 2,
+    /// covering all internal rotations.
+    fn all_small_queues() -> Vec<CircularQueue<u8>> {
+        let mut queues = Vec::new();
+        for capacity in 0..4 {
+            for pushes in 0..5 {
+                for mut n in 0..3usize.pow(pushes) {
+                    let mut q = CircularQueue::with_capacity(capacity);
+                    for _ in 0..pushes {
+                        q.push((n % 3) as u8);
+                        n /= 3;
+                    }
+                    queues.push(q);
+                }
+            }
+        }
+        queues
+    }
+
+    /// Returns queues of the given capacity holding `0..len` for every length, with every possible
+    /// wrap point.
+    pub(crate) fn wrapped_queues(capacity: usize) -> Vec<CircularQueue<usize>> {
+        let mut queues = Vec::new();
+        for len in 0..capacity + 1 {
+            for offset in 0..capacity {
+                let mut q = CircularQueue::with_capacity(capacity);
+                q.extend(0..offset);
+                q.extend(0..len);
+                if q.len() == len {
+                    queues.push(q);
+                }
+            }
+        }
+        queues
+    }
+
+    /// A hasher recording everything written into it.
+    struct RecordingHasher(Vec<u8>);
+
+    impl Hasher for RecordingHasher {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.extend_from_slice(bytes);
+        }
+    }
+
+    fn hash_input<T: Hash>(x: &T) -> Vec<u8> {
+        let mut hasher = RecordingHasher(Vec::new());
+        x.hash(&mut hasher);
+        hasher.0
+    }
+
+    #[test]
+    fn ord_and_hash_consistent_with_eq() {
+        let queues = all_small_queues();
+        let hashes: Vec<_> = queues.iter().map(hash_input).collect();
+        let vecs: Vec<Vec<_>> = queues.iter().map(|q| q.iter().cloned().collect()).collect();
+
+        for i in 0..queues.len() {
+            for j in 0..queues.len() {
+                let (a, b) = (&queues[i], &queues[j]);
+                let eq = a == b;
+
+                assert_eq!(a.cmp(b), vecs[i].cmp(&vecs[j]));
+                assert_eq!(a.partial_cmp(b), Some(a.cmp(b)));
+                assert_eq!(eq, a.cmp(b) == Ordering::Equal);
+                assert_eq!(eq, hashes[i] == hashes[j]);
+            }
+        }
+    }
+
+    #[test]
+    fn hash_ignores_rotation() {
+        let mut q1 = CircularQueue::with_capacity(3);
+        q1.push(1);
+        q1.push(2);
+        q1.push(3);
+
+        let mut q2 = CircularQueue::with_capacity(3);
+        q2.push(0);
+        q2.push(1);
+        q2.push(2);
+        q2.push(3);
+
+        assert_eq!(q1, q2);
+        assert_eq!(hash_input(&q1), hash_input(&q2));
+    }
+
+    #[test]
+    fn ord() {
+        let mut q1 = CircularQueue::with_capacity(3);
+        q1.push(1);
+        q1.push(2);
+
+        let mut q2 = CircularQueue::with_capacity(2);
+        q2.push(3);
+        assert!(q1 < q2); // [2, 1] < [3]
+
+        q2.clear();
+        q2.push(2);
+        assert!(q1 > q2); // [2, 1] > [2]
+
+        q2.push(0);
+        q2.push(1);
+        q2.push(2);
+        assert_eq!(q1.cmp(&q2), Ordering::Equal);
+    }
+
+    #[test]
+    fn slice_eq() {
+        let mut q = CircularQueue::with_capacity(3);
+        let empty: &[i32] = &[];
+        assert_eq!(q, empty);
+
+        q.push(1);
+        q.push(2);
+        assert_eq!(q, &[1, 2][..]);
+        assert_ne!(q, &[2, 1][..]);
+
+        q.push(3);
+        q.push(4);
+        q.push(5);
+        assert_eq!(q, &[3, 4, 5][..]);
+        assert_eq!(q, *vec![3, 4, 5].as_slice());
+        assert_ne!(q, &[3, 4][..]);
+        assert_ne!(q, &[4, 5, 3][..]);
+    }
+
+    #[test]
+    fn vec_eq() {
+        let mut q = CircularQueue::with_capacity(3);
+        assert_eq!(q, Vec::<i32>::new());
+
+        q.push(1);
+        q.push(2);
+        q.push(3);
+        q.push(4);
+        assert_eq!(q, vec![2, 3, 4]);
+        assert_ne!(q, vec![4, 3, 2]);
+        assert_ne!(q, vec![2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn vec_deque_eq() {
+        let mut q = CircularQueue::with_capacity(3);
+        let mut d = VecDeque::new();
+        assert_eq!(q, d);
+
+        for i in 0..5 {
+            q.push(i);
+            d.push_back(i);
+            if d.len() > 3 {
+                d.pop_front();
+            }
+            assert_eq!(q, d);
+        }
+
+        d.pop_front();
+        assert_ne!(q, d);
+    }
+
+    #[test]
+    fn eq_capacity() {
+        let mut q1 = CircularQueue::with_capacity(3);
+        q1.push(1);
+        q1.push(2);
+
+        let mut q2 = CircularQueue::with_capacity(2);
+        q2.push(0);
+        q2.push(1);
+        q2.push(2);
+        assert_eq!(q1, q2);
+        assert!(!q1.eq_capacity(&q2));
+
+        let mut q3 = CircularQueue::with_capacity(3);
+        q3.push(1);
+        q3.push(2);
+        assert!(q1.eq_capacity(&q3));
+
+        q3.push(3);
+        assert!(!q1.eq_capacity(&q3));
+    }
+
+    #[test]
+    fn reject_policy() {
+        let mut q = CircularQueue::with_capacity(2);
+        q.set_overflow_policy(OverflowPolicy::Reject);
+
+        assert_eq!(q.push(1), None);
+        assert_eq!(q.push(2), None);
+        assert_eq!(q.push(3), Some(3));
+        assert_eq!(q, vec![1, 2]);
+
+        q.set_overflow_policy(OverflowPolicy::Overwrite);
+        assert_eq!(q.push(3), Some(1));
+        assert_eq!(q, vec![2, 3]);
+
+        let mut q = CircularQueue::with_capacity(0);
+        q.set_overflow_policy(OverflowPolicy::Reject);
+        assert_eq!(q.push(1), Some(1));
+    }
+
+    #[test]
+    fn default() {
+        let mut q = CircularQueue::default();
+        assert_eq!(q.capacity(), 0);
+        assert_eq!(q.push(1), None);
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn extend() {
+        let mut q = CircularQueue::with_capacity(3);
+        q.extend(vec![1, 2]);
+        assert_eq!(q, vec![1, 2]);
+
+        q.extend(&[3, 4]);
+        assert_eq!(q, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn from_iter_with_capacity() {
+        let q = CircularQueue::from_iter_with_capacity(1..3, 3);
+        assert_eq!(q.capacity(), 3);
+        assert_eq!(q, vec![1, 2]);
+
+        let q = CircularQueue::from_iter_with_capacity(1..10, 3);
+        assert_eq!(q, vec![7, 8, 9]);
+        assert_eq!(q.total_pushed(), 0);
+        assert_eq!(q.total_evicted(), 0);
+    }
+
+    #[test]
+    fn collect() {
+        let q: CircularQueue<_> = (1..4).collect();
+        assert_eq!(q.capacity(), 3);
+        assert!(q.is_full());
+        assert_eq!(q, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn set_capacity() {
+        let mut q = CircularQueue::with_capacity(4);
+        q.push(1);
+        q.push(2);
+        q.push(3);
+        q.push(4);
+        q.push(5);
+        q.push(6);
 
+        q.set_capacity(4);
+        assert_eq!(q, vec![3, 4, 5, 6]);
+        q.push(7);
+        assert_eq!(q, vec![4, 5, 6, 7]);
+
+        q.set_capacity(2);
+        assert_eq!(q.capacity(), 2);
+        assert!(q.is_full());
+        assert_eq!(q, vec![6, 7]);
+        q.push(8);
+        assert_eq!(q, vec![7, 8]);
+
+        q.set_capacity(5);
+        assert!(q.data.capacity() >= 5);
+        assert_eq!(q, vec![7, 8]);
+        q.push(9);
+        q.push(10);
+        q.push(11);
+        assert_eq!(q, vec![7, 8, 9, 10, 11]);
+        q.push(12);
+        assert_eq!(q, vec![8, 9, 10, 11, 12]);
+
+        q.set_capacity(0);
         assert!(q.is_empty());
-        assert_eq!(q.iter().next(), None);
+        q.push(13);
+        assert!(q.is_empty());
+
+        q.set_capacity(1);
+        q.push(14);
+        assert_eq!(q, vec![14]);
     }
 
+    #[cfg(has_try_reserve)]
     #[test]
-    fn partially_full_queue() {
-        let mut q = CircularQueue::with_capacity(5);
+    fn try_set_capacity() {
+        let mut q = CircularQueue::with_capacity(3);
         q.push(1);
         q.push(2);
         q.push(3);
+        q.push(4);
 
-        assert!(!q.is_empty());
-        assert_eq!(q.len(), 3);
+        assert!(q.try_set_capacity(usize::MAX).is_err());
+        assert_eq!(q.capacity(), 3);
+        assert_eq!(q, vec![2, 3, 4]);
 
-        let res: Vec<_> = q.iter().map(|&x| x).collect();
-        assert_eq!(res, [3, 2, 1]);
+        q.try_set_capacity(2).unwrap();
+        assert_eq!(q, vec![3, 4]);
     }
 
     #[test]
-    fn full_queue() {
-        let mut q = CircularQueue::with_capacity(5);
+    fn into_vec() {
+        let mut q = CircularQueue::with_capacity(4);
         q.push(1);
         q.push(2);
         q.push(3);
+
+        let v = q.clone().into_vec();
+        assert_eq!(v, vec![3, 2, 1]);
+
         q.push(4);
         q.push(5);
+        let v = q.clone().into_vec();
+        assert_eq!(v, vec![5, 4, 3, 2]);
 
-        assert_eq!(q.len(), 5);
-
-        let res: Vec<_> = q.iter().map(|&x| x).collect();
-        assert_eq!(res, [5, 4, 3, 2, 1]);
+        q.push(6);
+        let v = q.into_vec();
+        assert_eq!(v, vec![6, 5, 4, 3]);
     }
 
     #[test]
-    fn over_full_queue() {
-        let mut q = CircularQueue::with_capacity(5);
+    fn into_vec_asc() {
+        let mut q = CircularQueue::with_capacity(4);
         q.push(1);
         q.push(2);
         q.push(3);
+
+        let v = q.clone().into_vec_asc();
+        assert_eq!(v, vec![1, 2, 3]);
+
         q.push(4);
         q.push(5);
-        q.push(6);
-        q.push(7);
-
-        assert_eq!(q.len(), 5);
+        let v = q.clone().into_vec_asc();
+        assert_eq!(v, vec![2, 3, 4, 5]);
 
-        let res: Vec<_> = q.iter().map(|&x| x).collect();
-        assert_eq!(res, [7, 6, 5, 4, 3]);
+        q.push(6);
+        let v = q.into_vec_asc();
+        assert_eq!(v, vec![3, 4, 5, 6]);
     }
 
     #[test]
-    fn clear() {
-        let mut q = CircularQueue::with_capacity(5);
+    fn into_boxed_slice() {
+        let mut q = CircularQueue::with_capacity(3);
         q.push(1);
         q.push(2);
         q.push(3);
         q.push(4);
-        q.push(5);
-        q.push(6);
-        q.push(7);
 
-        q.clear();
+        let b = q.into_boxed_slice();
+        assert_eq!(&*b, &[4, 3, 2]);
+    }
 
-        assert_eq!(q.len(), 0);
-        assert_eq!(q.iter().next(), None);
+    #[test]
+    fn from_vec() {
+        let mut q = CircularQueue::from(vec![1, 2, 3]);
+        assert_eq!(q.capacity(), 3);
+        assert!(q.is_full());
+        assert_eq!(q, vec![1, 2, 3]);
+
+        assert_eq!(q.push(4), Some(1));
+        assert_eq!(q, vec![2, 3, 4]);
+
+        let q = CircularQueue::<i32>::from(Vec::new());
+        assert_eq!(q.capacity(), 0);
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn from_vec_deque() {
+        let mut d = VecDeque::with_capacity(4);
+        d.push_back(1);
+        d.push_back(2);
+        d.push_back(3);
+        d.push_back(4);
+        d.pop_front();
+        d.push_back(5);
+
+        let mut q = CircularQueue::from(d.clone());
+        assert_eq!(q.capacity(), 4);
+        assert_eq!(q, d);
+
+        q.push(6);
+        assert_eq!(q, vec![3, 4, 5, 6]);
+    }
 
+    #[cfg(has_relaxed_orphan_rule)]
+    #[test]
+    fn vec_deque_from() {
+        let mut q = CircularQueue::with_capacity(3);
         q.push(1);
         q.push(2);
         q.push(3);
+        q.push(4);
 
-        assert_eq!(q.len(), 3);
-
-        let res: Vec<_> = q.iter().map(|&x| x).collect();
-        assert_eq!(res, [3, 2, 1]);
+        let d = VecDeque::from(q.clone());
+        assert_eq!(d, [2, 3, 4]);
+        assert_eq!(CircularQueue::from(d), q);
     }
 
+    #[cfg(has_relaxed_orphan_rule)]
     #[test]
-    fn mutable_iterator() {
-        let mut q = CircularQueue::with_capacity(5);
+    fn vec_from() {
+        let mut q = CircularQueue::with_capacity(3);
         q.push(1);
         q.push(2);
         q.push(3);
         q.push(4);
-        q.push(5);
-        q.push(6);
-        q.push(7);
 
-        for x in q.iter_mut() {
-            *x *= 2;
-        }
+        let v = Vec::from(q);
+        assert_eq!(v, vec![4, 3, 2]);
+    }
 
-        let res: Vec<_> = q.iter().map(|&x| x).collect();
-        assert_eq!(res, [14, 12, 10, 8, 6]);
+    #[test]
+    fn copy_to_slice() {
+        for q in all_small_queues() {
+            let expected: Vec<u8> = q.asc_iter().cloned().collect();
+
+            let mut buf = vec![0; q.len()];
+            q.copy_to_slice_asc(&mut buf);
+            assert_eq!(buf, expected);
+
+            for n in 0..q.len() + 1 {
+                let mut buf = vec![0; n];
+                q.copy_newest_n(&mut buf);
+                assert_eq!(buf[..], expected[q.len() - n..]);
+            }
+        }
     }
 
     #[test]
-    fn zero_sized() {
+    #[should_panic(expected = "destination length must be equal to the queue length")]
+    fn copy_to_slice_wrong_length() {
         let mut q = CircularQueue::with_capacity(3);
-        assert_eq!(q.capacity(), 3);
-
-        q.push(());
-        q.push(());
-        q.push(());
-        q.push(());
+        q.push(1);
+        q.copy_to_slice_asc(&mut [0, 0]);
+    }
 
-        assert_eq!(q.len(), 3);
+    #[test]
+    #[should_panic(expected = "destination length must not exceed the queue length")]
+    fn copy_newest_n_too_long() {
+        let mut q = CircularQueue::with_capacity(3);
+        q.push(1);
+        q.copy_newest_n(&mut [0, 0]);
+    }
 
-        let mut iter = q.iter();
-        assert_eq!(iter.next(), Some(&()));
-        assert_eq!(iter.next(), Some(&()));
-        assert_eq!(iter.next(), Some(&()));
-        assert_eq!(iter.next(), None);
+    #[test]
+    fn write_from_slice() {
+        let src: Vec<u8> = (10..20).collect();
+
+        for q in all_small_queues() {
+            for &policy in &[OverflowPolicy::Overwrite, OverflowPolicy::Reject] {
+                for n in 0..src.len() {
+                    let mut expected = q.clone();
+                    expected.set_overflow_policy(policy);
+                    let pushed = src[..n]
+                        .iter()
+                        .filter(|&&x| {
+                            expected.push(x) != Some(x) || policy == OverflowPolicy::Overwrite
+                        })
+                        .count();
+
+                    let mut actual = q.clone();
+                    actual.set_overflow_policy(policy);
+                    assert_eq!(actual.write_from_slice(&src[..n]), pushed);
+                    assert!(actual.eq_capacity(&expected));
+
+                    // The queue must keep working afterwards.
+                    assert_eq!(actual.push(0), expected.push(0));
+                    assert_eq!(actual, expected);
+                }
+            }
+        }
     }
 
     #[test]
-    fn empty_queue_eq() {
-        let q1 = CircularQueue::<i32>::with_capacity(5);
-        let q2 = CircularQueue::<i32>::with_capacity(5);
-        assert_eq!(q1, q2);
+    fn write_from_slice_lazy() {
+        let mut q = CircularQueue::builder()
+            .capacity(8)
+            .preallocation(Preallocation::Lazy)
+            .build();
+
+        assert_eq!(q.write_from_slice(&[1, 2, 3]), 3);
+        assert_eq!(q.write_from_slice(&[4, 5, 6, 7, 8, 9]), 6);
+        assert_eq!(q, vec![2, 3, 4, 5, 6, 7, 8, 9]);
+    }
 
-        let q3 = CircularQueue::<i32>::with_capacity(6);
-        assert_eq!(q1, q3); // Capacity doesn't matter as long as the same elements are yielded.
+    /// Returns a queue recording its evictions into the returned vector.
+    fn recording_queue(
+        capacity: usize,
+    ) -> (
+        CircularQueue<i32>,
+        std::sync::Arc<std::sync::Mutex<Vec<i32>>>,
+    ) {
+        let evicted = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut q = CircularQueue::with_capacity(capacity);
+        let evicted_in_callback = evicted.clone();
+        q.set_on_evict(move |&x| evicted_in_callback.lock().unwrap().push(x));
+        (q, evicted)
     }
 
     #[test]
-    fn partially_full_queue_eq() {
-        let mut q1 = CircularQueue::with_capacity(5);
-        q1.push(1);
-        q1.push(2);
-        q1.push(3);
+    fn on_evict() {
+        let (mut q, evicted) = recording_queue(3);
+        for x in 1..6 {
+            q.push(x);
+        }
+        assert_eq!(*evicted.lock().unwrap(), vec![1, 2]);
+        assert_eq!(q.total_pushed(), 5);
+        assert_eq!(q.total_evicted(), 2);
 
-        let mut q2 = CircularQueue::with_capacity(5);
-        q2.push(1);
-        q2.push(2);
-        assert_ne!(q1, q2);
+        q.set_capacity(1);
+        assert_eq!(*evicted.lock().unwrap(), vec![1, 2, 3, 4]);
 
-        q2.push(3);
-        assert_eq!(q1, q2);
+        q.set_capacity(3);
+        q.push(6);
+        q.clear();
+        assert_eq!(*evicted.lock().unwrap(), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(q.total_pushed(), 6);
+        assert_eq!(q.total_evicted(), 6);
 
-        q2.push(4);
-        assert_ne!(q1, q2);
+        q.remove_on_evict();
+        q.push(7);
+        q.clear();
+        assert_eq!(evicted.lock().unwrap().len(), 6);
+        assert_eq!(q.total_evicted(), 7);
     }
 
     #[test]
-    fn full_queue_eq() {
-        let mut q1 = CircularQueue::with_capacity(5);
-        q1.push(1);
-        q1.push(2);
-        q1.push(3);
-        q1.push(4);
-        q1.push(5);
+    fn on_evict_not_sync() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
 
-        let mut q2 = CircularQueue::with_capacity(5);
-        q2.push(1);
-        q2.push(2);
-        q2.push(3);
-        q2.push(4);
-        q2.push(5);
+        // `Cell` is `Send` but not `Sync`.
+        let count = std::cell::Cell::new(0);
+        let mut q = CircularQueue::with_capacity(1);
+        q.set_on_evict(move |_| count.set(count.get() + 1));
+        q.push(1);
+        q.push(2);
+        assert_send_sync(&q);
 
-        assert_eq!(q1, q2);
+        // The clone doesn't get the callback.
+        let mut clone = q.clone();
+        assert!(!clone.evictions.has_callback());
+        clone.push(3);
+        assert_eq!(clone.total_evicted(), 2);
     }
 
     #[test]
-    fn over_full_queue_eq() {
-        let mut q1 = CircularQueue::with_capacity(5);
-        q1.push(1);
-        q1.push(2);
-        q1.push(3);
-        q1.push(4);
-        q1.push(5);
-        q1.push(6);
-        q1.push(7);
-
-        let mut q2 = CircularQueue::with_capacity(5);
-        q2.push(1);
-        q2.push(2);
-        q2.push(3);
-        q2.push(4);
-        q2.push(5);
-        q2.push(6);
-        assert_ne!(q1, q2);
-
-        q2.push(7);
-        assert_eq!(q1, q2);
-
-        q2.push(8);
-        assert_ne!(q1, q2);
-
-        q2.push(3);
-        q2.push(4);
-        q2.push(5);
-        q2.push(6);
-        q2.push(7);
-        assert_eq!(q1, q2);
+    fn on_evict_zero_capacity_and_reject() {
+        let (mut q, evicted) = recording_queue(0);
+        assert_eq!(q.push(1), None);
+        assert_eq!(*evicted.lock().unwrap(), vec![1]);
+        assert_eq!(q.total_pushed(), 1);
+
+        let (mut q, evicted) = recording_queue(1);
+        q.set_overflow_policy(OverflowPolicy::Reject);
+        q.push(1);
+        assert_eq!(q.push(2), Some(2));
+        assert!(evicted.lock().unwrap().is_empty());
+        assert_eq!(q.total_pushed(), 1);
+        assert_eq!(q.total_evicted(), 0);
     }
 
     #[test]
-    fn clear_eq() {
-        let mut q1 = CircularQueue::with_capacity(5);
-        q1.push(1);
-        q1.push(2);
-        q1.push(3);
-        q1.push(4);
-        q1.push(5);
-        q1.push(6);
-        q1.push(7);
-        q1.clear();
+    fn on_evict_write_from_slice() {
+        for capacity in 0..5 {
+            for prefill in 0..capacity + 1 {
+                for n in 0..8 {
+                    let src: Vec<i32> = (100..100 + n).collect();
+
+                    let (mut expected, expected_evicted) = recording_queue(capacity);
+                    let (mut actual, actual_evicted) = recording_queue(capacity);
+                    for x in 0..prefill as i32 {
+                        expected.push(x);
+                        actual.push(x);
+                    }
+
+                    for &x in &src {
+                        expected.push(x);
+                    }
+                    actual.write_from_slice(&src);
+
+                    assert_eq!(
+                        *actual_evicted.lock().unwrap(),
+                        *expected_evicted.lock().unwrap()
+                    );
+                    assert_eq!(actual.total_pushed(), expected.total_pushed());
+                    assert_eq!(actual.total_evicted(), expected.total_evicted());
+                }
+            }
+        }
+    }
 
-        let mut q2 = CircularQueue::with_capacity(5);
-        assert_eq!(q1, q2);
+    #[test]
+    fn clone_keeps_counters() {
+        let mut q = CircularQueue::with_capacity(1);
+        q.push(1);
+        q.push(2);
 
-        q2.push(1);
-        q2.clear();
-        assert_eq!(q1, q2);
+        let c = q.clone();
+        assert_eq!(c.total_pushed(), 2);
+        assert_eq!(c.total_evicted(), 1);
     }
 
     #[test]
-    fn zero_sized_eq() {
-        let mut q1 = CircularQueue::with_capacity(3);
-        q1.push(());
-        q1.push(());
-        q1.push(());
-        q1.push(());
+    fn newest_oldest() {
+        for mut q in all_small_queues() {
+            let newest = q.iter().next().cloned();
+            let oldest = q.asc_iter().next().cloned();
+            assert_eq!(q.newest().cloned(), newest);
+            assert_eq!(q.oldest().cloned(), oldest);
+
+            if let Some(x) = q.newest_mut() {
+                *x = 10;
+            }
+            if let Some(x) = q.oldest_mut() {
+                *x += 20;
+            }
+            if q.len() > 1 {
+                assert_eq!(q.newest(), Some(&10));
+                assert_eq!(q.oldest(), Some(&(oldest.unwrap() + 20)));
+            } else if q.len() == 1 {
+                assert_eq!(q.newest(), Some(&30));
+            }
+        }
+    }
 
-        let mut q2 = CircularQueue::with_capacity(3);
-        q2.push(());
-        q2.push(());
-        assert_ne!(q1, q2);
+    #[test]
+    fn push_with() {
+        let mut q = CircularQueue::with_capacity(2);
+        assert!(q
+            .push_with(|old| {
+                assert_eq!(old, None);
+                1
+            })
+            .is_ok());
+        assert!(q.push_with(|_| 2).is_ok());
+        assert!(q
+            .push_with(|old| {
+                assert_eq!(old, Some(1));
+                3
+            })
+            .is_ok());
+        assert_eq!(q, vec![2, 3]);
+        assert_eq!(q.total_pushed(), 3);
+        assert_eq!(q.total_evicted(), 1);
+
+        // A rejected push hands the closure back without calling it.
+        q.set_overflow_policy(OverflowPolicy::Reject);
+        let mut called = false;
+        let rejected = q.push_with(|_| {
+            called = true;
+            4
+        });
+        match rejected {
+            Ok(_) => panic!("the push wasn't rejected"),
+            Err(f) => assert_eq!(f(None), 4),
+        }
+        assert!(called);
+        assert_eq!(q, vec![2, 3]);
+        assert_eq!(q.total_pushed(), 3);
+    }
 
-        q2.push(());
-        assert_eq!(q1, q2);
+    #[test]
+    fn push_with_reuses_buffer() {
+        let mut q: CircularQueue<Vec<u8>> = CircularQueue::with_capacity(2);
+        q.push(Vec::with_capacity(64));
+        q.push(Vec::new());
+        let ptr = q.oldest().unwrap().as_ptr();
+
+        assert!(q
+            .push_with(|old| {
+                let mut buffer = old.unwrap();
+                buffer.extend_from_slice(b"reused");
+                buffer
+            })
+            .is_ok());
+
+        let newest = q.newest().unwrap();
+        assert_eq!(newest.as_ptr(), ptr);
+        assert_eq!(newest.capacity(), 64);
+        assert_eq!(&newest[..], b"reused");
+    }
 
-        q2.push(());
-        assert_eq!(q1, q2);
+    #[test]
+    fn push_with_panic() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        use std::rc::Rc;
+
+        for offset in 0..4 {
+            let mut q = CircularQueue::with_capacity(4);
+            for x in 0..4 + offset {
+                q.push(Rc::new(x));
+            }
+            let mut expected: Vec<usize> = (offset + 1..offset + 4).collect();
+
+            let oldest = q.oldest().unwrap().clone();
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                let _ = q.push_with(|_| panic!("can't create the element"));
+            }));
+            assert!(result.is_err());
+
+            // The overwritten element was dropped exactly once.
+            assert_eq!(Rc::strong_count(&oldest), 1);
+            assert_eq!(q.capacity(), 4);
+            assert_eq!(q.asc_iter().map(|x| **x).collect::<Vec<_>>(), expected);
+
+            q.push(Rc::new(100));
+            expected.push(100);
+            q.push(Rc::new(101));
+            expected.remove(0);
+            expected.push(101);
+            assert_eq!(q.asc_iter().map(|x| **x).collect::<Vec<_>>(), expected);
+        }
+    }
 
-        q2.push(());
-        assert_eq!(q1, q2);
+    #[test]
+    fn push_or_update() {
+        let mut q = CircularQueue::with_capacity(2);
+
+        // An empty queue has nothing to update.
+        assert!(q.push_or_update(|_| unreachable!(), |_| 1).is_ok());
+
+        assert!(q.push_or_update(|_| false, |_| 2).is_ok());
+        assert!(q
+            .push_or_update(
+                |last| {
+                    *last += 10;
+                    true
+                },
+                |_| unreachable!()
+            )
+            .is_ok());
+        assert_eq!(q, vec![1, 12]);
+
+        assert!(q
+            .push_or_update(
+                |_| false,
+                |old| {
+                    assert_eq!(old, Some(1));
+                    3
+                }
+            )
+            .is_ok());
+        assert_eq!(q, vec![12, 3]);
+        assert_eq!(q.total_pushed(), 3);
+
+        // Updating still works when the queue rejects new elements.
+        q.set_overflow_policy(OverflowPolicy::Reject);
+        assert!(q.push_or_update(|_| true, |_| unreachable!()).is_ok());
+        assert!(q.push_or_update(|_| false, |_| 4).is_err());
+        assert_eq!(q, vec![12, 3]);
     }
 
     #[test]
-    fn into_vec() {
-        let mut q = CircularQueue::with_capacity(4);
+    fn next_slot_mut() {
+        let mut q = CircularQueue::with_capacity(3);
+        assert_eq!(q.next_slot_mut(), None);
         q.push(1);
         q.push(2);
+        assert_eq!(q.next_slot_mut(), None);
         q.push(3);
 
-        let v = q.clone().into_vec();
-        assert_eq!(v, vec![3, 2, 1]);
+        for x in 4..9 {
+            let slot = q.next_slot_mut().unwrap();
+            assert_eq!(*slot, x - 3);
+            *slot = x;
+            q.commit();
+            assert_eq!(q.newest(), Some(&x));
+        }
+        assert_eq!(q, vec![6, 7, 8]);
+        assert_eq!(q.total_pushed(), 8);
+        assert_eq!(q.total_evicted(), 5);
 
-        q.push(4);
-        q.push(5);
-        let v = q.clone().into_vec();
-        assert_eq!(v, vec![5, 4, 3, 2]);
+        // Without committing, the oldest element is overwritten in place and stays the oldest.
+        *q.next_slot_mut().unwrap() = 0;
+        assert_eq!(q, vec![0, 7, 8]);
+        assert_eq!(q.total_pushed(), 8);
 
-        q.push(6);
-        let v = q.into_vec();
-        assert_eq!(v, vec![6, 5, 4, 3]);
+        q.set_overflow_policy(OverflowPolicy::Reject);
+        assert_eq!(q.next_slot_mut(), None);
+
+        let mut q = CircularQueue::<i32>::with_capacity(0);
+        assert_eq!(q.next_slot_mut(), None);
     }
 
-    #[cfg(has_relaxed_orphan_rule)]
     #[test]
-    fn vec_from() {
+    #[should_panic(expected = "there is no slot to commit")]
+    fn commit_not_full() {
         let mut q = CircularQueue::with_capacity(3);
         q.push(1);
-        q.push(2);
-        q.push(3);
-        q.push(4);
-
-        let v = Vec::from(q);
-        assert_eq!(v, vec![4, 3, 2]);
+        q.commit();
     }
 }
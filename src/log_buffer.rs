@@ -0,0 +1,439 @@
+//! Bounded in-memory sinks for the `log` and `tracing` crates.
+
+#[cfg(feature = "log_support")]
+extern crate log;
+#[cfg(feature = "tracing_support")]
+extern crate tracing;
+#[cfg(feature = "tracing_support")]
+extern crate tracing_subscriber;
+
+use super::*;
+
+use std::borrow::ToOwned;
+use std::string::String;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+#[cfg(feature = "log_support")]
+use std::format;
+
+#[cfg(feature = "tracing_support")]
+use self::tracing::field::{Field, Visit};
+#[cfg(feature = "tracing_support")]
+use self::tracing::{Event, Level, Subscriber};
+#[cfg(feature = "tracing_support")]
+use self::tracing_subscriber::layer::{Context, Layer};
+
+/// The severity of a `LogRecord`, ordered from the most severe to the least severe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    /// Errors.
+    Error,
+    /// Warnings.
+    Warn,
+    /// Informational messages.
+    Info,
+    /// Debugging messages.
+    Debug,
+    /// Very verbose debugging messages.
+    Trace,
+}
+
+impl LogLevel {
+    const ALL: [LogLevel; 5] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+}
+
+#[cfg(feature = "log_support")]
+impl From<log::Level> for LogLevel {
+    #[inline]
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => LogLevel::Error,
+            log::Level::Warn => LogLevel::Warn,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Debug => LogLevel::Debug,
+            log::Level::Trace => LogLevel::Trace,
+        }
+    }
+}
+
+#[cfg(feature = "tracing_support")]
+impl From<Level> for LogLevel {
+    #[inline]
+    fn from(level: Level) -> Self {
+        if level == Level::ERROR {
+            LogLevel::Error
+        } else if level == Level::WARN {
+            LogLevel::Warn
+        } else if level == Level::INFO {
+            LogLevel::Info
+        } else if level == Level::DEBUG {
+            LogLevel::Debug
+        } else {
+            LogLevel::Trace
+        }
+    }
+}
+
+/// A formatted record kept in a `LogBuffer`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogRecord {
+    /// When the record was stored.
+    pub time: SystemTime,
+    /// The severity of the record.
+    pub level: LogLevel,
+    /// The target of the record, usually the module path it came from.
+    pub target: String,
+    /// The formatted message, followed by any `tracing` fields as `name=value`.
+    pub message: String,
+}
+
+/// A bounded in-memory log keeping the most recent records.
+///
+/// `LogBuffer` implements `log::Log` under the `log_support` feature and
+/// `tracing_subscriber::Layer` under the `tracing_support` feature. It is a cheap handle to
+/// shared state, so a clone can be installed as the logger while another one is used to look at
+/// the records, for example for a crash report.
+///
+/// Records more verbose than the maximum level are not stored.
+///
+/// # Examples
+///
+/// ```
+/// use circular_queue::{LogBuffer, LogLevel, LogRecord};
+/// use std::time::SystemTime;
+///
+/// let buffer = LogBuffer::new(2);
+/// for message in &["one", "two", "three"] {
+///     buffer.push(LogRecord {
+///         time: SystemTime::now(),
+///         level: LogLevel::Info,
+///         target: "example".to_owned(),
+///         message: message.to_string(),
+///     });
+/// }
+///
+/// let messages: Vec<_> = buffer.snapshot().into_iter().map(|r| r.message).collect();
+/// assert_eq!(messages, ["two", "three"]);
+/// ```
+#[derive(Clone)]
+pub struct LogBuffer {
+    inner: Arc<LogBufferInner>,
+}
+
+struct LogBufferInner {
+    records: Mutex<CircularQueue<LogRecord>>,
+    max_level: AtomicUsize,
+}
+
+impl LogBuffer {
+    /// Creates a buffer keeping up to `capacity` records of all levels.
+    #[inline]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(LogBufferInner {
+                records: Mutex::new(CircularQueue::with_capacity(capacity)),
+                max_level: AtomicUsize::new(LogLevel::Trace as usize),
+            }),
+        }
+    }
+
+    /// Returns the most verbose level of the records that are stored.
+    #[inline]
+    pub fn max_level(&self) -> LogLevel {
+        LogLevel::ALL[self.inner.max_level.load(AtomicOrdering::Relaxed)]
+    }
+
+    /// Sets the most verbose level of the records that are stored.
+    #[inline]
+    pub fn set_max_level(&self, level: LogLevel) {
+        self.inner
+            .max_level
+            .store(level as usize, AtomicOrdering::Relaxed);
+    }
+
+    /// Returns the maximum number of records kept.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.records().capacity()
+    }
+
+    #[inline]
+    fn records<'a>(&'a self) -> MutexGuard<'a, CircularQueue<LogRecord>> {
+        // A panic while holding the lock can't leave the queue in an inconsistent state.
+        match self.inner.records.lock() {
+            Ok(records) => records,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Stores a record unless it's more verbose than the maximum level, overwriting the oldest
+    /// one if the buffer is full.
+    #[inline]
+    pub fn push(&self, record: LogRecord) {
+        if record.level <= self.max_level() {
+            self.records().push(record);
+        }
+    }
+
+    /// Returns copies of the stored records, going from the oldest ones to the newest ones.
+    #[inline]
+    pub fn snapshot(&self) -> Vec<LogRecord> {
+        self.records().asc_iter().cloned().collect()
+    }
+
+    /// Returns copies of the stored records that are at most as verbose as `level`, going from
+    /// the oldest ones to the newest ones.
+    #[inline]
+    pub fn snapshot_level(&self, level: LogLevel) -> Vec<LogRecord> {
+        self.records()
+            .asc_iter()
+            .filter(|record| record.level <= level)
+            .cloned()
+            .collect()
+    }
+
+    /// Removes all stored records.
+    #[inline]
+    pub fn clear(&self) {
+        self.records().clear();
+    }
+}
+
+impl fmt::Debug for LogBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LogBuffer")
+            .field("records", &*self.records())
+            .field("max_level", &self.max_level())
+            .finish()
+    }
+}
+
+#[cfg(feature = "log_support")]
+impl log::Log for LogBuffer {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        LogLevel::from(metadata.level()) <= self.max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !log::Log::enabled(self, record.metadata()) {
+            return;
+        }
+
+        // Format before taking the lock in case formatting logs something.
+        let message = format!("{}", record.args());
+        self.push(LogRecord {
+            time: SystemTime::now(),
+            level: record.level().into(),
+            target: record.target().to_owned(),
+            message,
+        });
+    }
+
+    #[inline]
+    fn flush(&self) {}
+}
+
+/// Formats the fields of a `tracing` event.
+#[cfg(feature = "tracing_support")]
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+#[cfg(feature = "tracing_support")]
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        use std::fmt::Write;
+
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            if !self.fields.is_empty() {
+                self.fields.push(' ');
+            }
+            let _ = write!(self.fields, "{}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(feature = "tracing_support")]
+impl<S: Subscriber> Layer<S> for LogBuffer {
+    fn on_event(&self, event: &Event, _ctx: Context<S>) {
+        let metadata = event.metadata();
+        let level = LogLevel::from(*metadata.level());
+        if level > self.max_level() {
+            return;
+        }
+
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let mut message = visitor.message;
+        if !visitor.fields.is_empty() {
+            if !message.is_empty() {
+                message.push(' ');
+            }
+            message.push_str(&visitor.fields);
+        }
+
+        self.push(LogRecord {
+            time: SystemTime::now(),
+            level,
+            target: metadata.target().to_owned(),
+            message,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(records: Vec<LogRecord>) -> Vec<String> {
+        records.into_iter().map(|r| r.message).collect()
+    }
+
+    fn record(level: LogLevel, message: &str) -> LogRecord {
+        LogRecord {
+            time: SystemTime::now(),
+            level,
+            target: "test".to_owned(),
+            message: message.to_owned(),
+        }
+    }
+
+    #[test]
+    fn bounded() {
+        let buffer = LogBuffer::new(3);
+        assert_eq!(buffer.capacity(), 3);
+
+        for &m in &["a", "b", "c", "d"] {
+            buffer.push(record(LogLevel::Info, m));
+        }
+        assert_eq!(messages(buffer.snapshot()), ["b", "c", "d"]);
+
+        buffer.clear();
+        assert!(buffer.snapshot().is_empty());
+    }
+
+    #[test]
+    fn levels() {
+        let buffer = LogBuffer::new(8);
+        buffer.set_max_level(LogLevel::Debug);
+        assert_eq!(buffer.max_level(), LogLevel::Debug);
+
+        buffer.push(record(LogLevel::Error, "error"));
+        buffer.push(record(LogLevel::Trace, "trace"));
+        buffer.push(record(LogLevel::Info, "info"));
+        buffer.push(record(LogLevel::Debug, "debug"));
+
+        assert_eq!(messages(buffer.snapshot()), ["error", "info", "debug"]);
+        assert_eq!(
+            messages(buffer.snapshot_level(LogLevel::Info)),
+            ["error", "info"]
+        );
+    }
+
+    #[test]
+    fn shared_between_clones() {
+        let buffer = LogBuffer::new(2);
+        let logger = buffer.clone();
+        logger.push(record(LogLevel::Warn, "from the logger"));
+        assert_eq!(messages(buffer.snapshot()), ["from the logger"]);
+    }
+
+    #[cfg(feature = "log_support")]
+    #[test]
+    fn log() {
+        use self::log::{Level, Log, Record};
+
+        let buffer = LogBuffer::new(2);
+        buffer.set_max_level(LogLevel::Info);
+
+        buffer.log(
+            &Record::builder()
+                .level(Level::Info)
+                .target("app::net")
+                .args(format_args!("connected to {}", "example.com"))
+                .build(),
+        );
+        buffer.log(
+            &Record::builder()
+                .level(Level::Debug)
+                .args(format_args!("too verbose"))
+                .build(),
+        );
+
+        let records = buffer.snapshot();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].level, LogLevel::Info);
+        assert_eq!(records[0].target, "app::net");
+        assert_eq!(records[0].message, "connected to example.com");
+    }
+
+    #[cfg(feature = "tracing_support")]
+    #[test]
+    fn tracing() {
+        use self::tracing::span::{Attributes, Id, Record};
+        use self::tracing::Metadata;
+        use self::tracing_subscriber::layer::SubscriberExt;
+
+        /// A subscriber doing nothing, for the layer to sit on.
+        struct NullSubscriber;
+
+        impl Subscriber for NullSubscriber {
+            fn enabled(&self, _: &Metadata) -> bool {
+                true
+            }
+
+            fn new_span(&self, _: &Attributes) -> Id {
+                Id::from_u64(1)
+            }
+
+            fn record(&self, _: &Id, _: &Record) {}
+
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+            fn event(&self, _: &Event) {}
+
+            fn enter(&self, _: &Id) {}
+
+            fn exit(&self, _: &Id) {}
+        }
+
+        let buffer = LogBuffer::new(4);
+        buffer.set_max_level(LogLevel::Debug);
+
+        let subscriber = NullSubscriber.with(buffer.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(target: "app::db", retries = 3, "query {}", "failed");
+            tracing::info!(user = "alice");
+            tracing::trace!("too verbose");
+        });
+
+        let records = buffer.snapshot();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].level, LogLevel::Warn);
+        assert_eq!(records[0].target, "app::db");
+        assert_eq!(records[0].message, "query failed retries=3");
+        assert_eq!(records[1].message, "user=\"alice\"");
+    }
+}
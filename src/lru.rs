@@ -0,0 +1,311 @@
+//! A bounded map that evicts the least recently used entry.
+
+use super::*;
+
+#[cfg(all(has_extern_crate_alloc, feature = "std"))]
+use std::collections::hash_map::RandomState;
+#[cfg(not(has_extern_crate_alloc))]
+use std::collections::hash_map::RandomState;
+
+/// A map holding at most `capacity` entries, where inserting into a full map evicts the least
+/// recently used entry.
+///
+/// The entries are stored in a `CircularQueue` going from the least recently used to the most
+/// recently used one. Both `get()` and `insert()` move an entry to the newest position, while
+/// `peek()` leaves the order untouched.
+///
+/// Every operation looking up a key takes O(capacity) time: the entry is found by scanning the
+/// map from the most recently used one, and moving it to the newest position shifts the entries
+/// that were used after it. The map is meant for small capacities.
+///
+/// Keys are looked up through a `RecentSetIndex`. The default `LinearIndex` only requires them to
+/// implement `Eq`. With the `std` feature, `HashLruRing` uses a `HashIndex` instead, which
+/// requires them to implement `Hash` and finds out that a key is missing without scanning.
+///
+/// # Examples
+///
+/// ```
+/// use circular_queue::LruRing;
+///
+/// let mut sessions: LruRing<u32, &str> = LruRing::with_capacity(2);
+/// sessions.insert(1, "alice");
+/// sessions.insert(2, "bob");
+///
+/// // Looking up a session marks it as recently used.
+/// assert_eq!(sessions.get(&1), Some(&"alice"));
+///
+/// // So the least recently used one is evicted.
+/// assert_eq!(sessions.insert(3, "carol"), Some((2, "bob")));
+/// assert_eq!(
+///     sessions.iter().collect::<Vec<_>>(),
+///     [&(3, "carol"), &(1, "alice")]
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct LruRing<K, V, X = LinearIndex> {
+    pub(crate) queue: CircularQueue<(K, V)>,
+    index: X,
+}
+
+/// An `LruRing` looking up keys through a `HashIndex`.
+#[cfg(any(feature = "std", not(has_extern_crate_alloc)))]
+pub type HashLruRing<K, V, S = RandomState> = LruRing<K, V, HashIndex<S>>;
+
+impl<K: Eq, V, X: RecentSetIndex<K>> LruRing<K, V, X> {
+    /// Constructs a new, empty `LruRing` with the requested capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self
+    where
+        X: Default,
+    {
+        Self::with_capacity_and_index(capacity, X::default())
+    }
+
+    /// Constructs a new, empty `LruRing` with the requested capacity and key index.
+    #[inline]
+    pub fn with_capacity_and_index(capacity: usize, index: X) -> Self {
+        LruRing {
+            queue: CircularQueue::with_capacity(capacity),
+            index,
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if the map contains no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Returns the capacity of the map.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+
+    /// Returns the underlying queue of entries.
+    #[inline]
+    pub fn as_queue(&self) -> &CircularQueue<(K, V)> {
+        &self.queue
+    }
+
+    /// Converts the map into the underlying queue of entries.
+    #[inline]
+    pub fn into_queue(self) -> CircularQueue<(K, V)> {
+        self.queue
+    }
+
+    /// Returns the index of the entry with `key` counting from the oldest one.
+    #[inline]
+    fn position(&self, key: &K) -> Option<usize> {
+        if !self.index.may_contain(key) {
+            return None;
+        }
+
+        self.queue
+            .position_from_newest(|entry| entry.0 == *key)
+            .map(|i| self.len() - 1 - i)
+    }
+
+    /// Returns `true` if the map contains an entry with `key`.
+    #[inline]
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Returns the value of the entry with `key` without marking it as recently used.
+    #[inline]
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        self.position(key)
+            .map(|index| &self.queue.get(index).unwrap().1)
+    }
+
+    /// Returns the value of the entry with `key` and marks it as the most recently used one.
+    #[inline]
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    /// Returns a mutable reference to the value of the entry with `key` and marks it as the most
+    /// recently used one.
+    #[inline]
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        match self.position(key) {
+            Some(index) => {
+                self.queue.move_to_newest(index);
+                Some(&mut self.queue.newest_mut().unwrap().1)
+            }
+            None => None,
+        }
+    }
+
+    /// Inserts an entry, making it the most recently used one.
+    ///
+    /// If the map already contains an entry with `key`, it's replaced and returned. Otherwise, if
+    /// the map is full, the least recently used entry is evicted and returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::{LinearIndex, LruRing};
+    ///
+    /// let mut ring: LruRing<&str, i32, LinearIndex> = LruRing::with_capacity(2);
+    /// assert_eq!(ring.insert("a", 1), None);
+    /// assert_eq!(ring.insert("b", 2), None);
+    ///
+    /// // Inserting an existing key replaces its entry.
+    /// assert_eq!(ring.insert("a", 3), Some(("a", 1)));
+    ///
+    /// // "b" is now the least recently used entry.
+    /// assert_eq!(ring.insert("c", 4), Some(("b", 2)));
+    /// assert_eq!(ring.peek(&"a"), Some(&3));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Popped<(K, V)> {
+        if self.capacity() == 0 {
+            let entry = (key, value);
+            self.queue.evictions.pushed(1);
+            self.queue.evictions.evict(&entry);
+            return Some(entry);
+        }
+
+        if let Some(index) = self.position(&key) {
+            self.queue.move_to_newest(index);
+            let old = replace(self.queue.newest_mut().unwrap(), (key, value));
+            self.queue.evictions.pushed(1);
+            self.queue.evictions.evict(&old);
+            return Some(old);
+        }
+
+        self.index.insert(&key);
+        let old = self.queue.push((key, value));
+        if let Some((ref key, _)) = old {
+            self.index.remove(key);
+        }
+        old
+    }
+
+    /// Removes the entry with `key` from the map and returns its value.
+    ///
+    /// The remaining entries keep their order.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        match self.position(key) {
+            Some(index) => {
+                let (key, value) = self.queue.remove_at(index);
+                self.index.remove(&key);
+                Some(value)
+            }
+            None => None,
+        }
+    }
+
+    /// Removes all entries from the map.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.queue.clear();
+        self.index.clear();
+    }
+
+    /// Returns an iterator over the map's entries, going from the most recently used ones to the
+    /// least recently used ones.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> Iter<'a, (K, V)> {
+        self.queue.iter()
+    }
+
+    /// Returns an iterator over the map's entries, going from the least recently used ones to the
+    /// most recently used ones.
+    #[inline]
+    pub fn asc_iter<'a>(&'a self) -> AscIter<'a, (K, V)> {
+        self.queue.asc_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(has_extern_crate_alloc)]
+    use alloc::vec;
+
+    fn entries<X: RecentSetIndex<i32>>(ring: &LruRing<i32, char, X>) -> Vec<(i32, char)> {
+        ring.asc_iter().cloned().collect()
+    }
+
+    fn check<X: RecentSetIndex<i32> + Default>() {
+        let mut ring: LruRing<i32, char, X> = LruRing::with_capacity(3);
+        assert_eq!(ring.insert(1, 'a'), None);
+        assert_eq!(ring.insert(2, 'b'), None);
+        assert_eq!(ring.insert(3, 'c'), None);
+
+        // Peeking doesn't change the order.
+        assert_eq!(ring.peek(&1), Some(&'a'));
+        assert_eq!(ring.peek(&4), None);
+        assert_eq!(entries(&ring), [(1, 'a'), (2, 'b'), (3, 'c')]);
+
+        assert_eq!(ring.get(&1), Some(&'a'));
+        assert_eq!(ring.get(&4), None);
+        assert_eq!(entries(&ring), [(2, 'b'), (3, 'c'), (1, 'a')]);
+
+        assert_eq!(ring.insert(4, 'd'), Some((2, 'b')));
+        assert!(!ring.contains_key(&2));
+        assert_eq!(ring.insert(3, 'e'), Some((3, 'c')));
+        assert_eq!(entries(&ring), [(1, 'a'), (4, 'd'), (3, 'e')]);
+
+        *ring.get_mut(&4).unwrap() = 'f';
+        assert_eq!(entries(&ring), [(1, 'a'), (3, 'e'), (4, 'f')]);
+        assert_eq!(
+            ring.iter().cloned().collect::<Vec<_>>(),
+            [(4, 'f'), (3, 'e'), (1, 'a')]
+        );
+
+        assert_eq!(ring.remove(&3), Some('e'));
+        assert_eq!(ring.remove(&3), None);
+        assert_eq!(entries(&ring), [(1, 'a'), (4, 'f')]);
+        assert_eq!(ring.insert(5, 'g'), None);
+        assert_eq!(ring.insert(6, 'h'), Some((1, 'a')));
+        assert_eq!(entries(&ring), [(4, 'f'), (5, 'g'), (6, 'h')]);
+
+        ring.clear();
+        assert!(ring.is_empty());
+        assert!(!ring.contains_key(&4));
+        assert_eq!(ring.insert(4, 'i'), None);
+
+        let mut ring: LruRing<i32, char, X> = LruRing::with_capacity(0);
+        assert_eq!(ring.insert(1, 'a'), Some((1, 'a')));
+        assert_eq!(ring.get(&1), None);
+        assert_eq!(ring.as_queue().total_pushed(), 1);
+        assert_eq!(ring.as_queue().total_evicted(), 1);
+    }
+
+    #[test]
+    fn linear() {
+        check::<LinearIndex>();
+    }
+
+    #[cfg(any(feature = "std", not(has_extern_crate_alloc)))]
+    #[test]
+    fn hashed() {
+        check::<HashIndex>();
+
+        let mut ring: HashLruRing<i32, char> = HashLruRing::with_capacity(1);
+        ring.insert(1, 'a');
+        assert_eq!(ring.insert(2, 'b'), Some((1, 'a')));
+    }
+
+    #[test]
+    fn counters() {
+        let mut ring: LruRing<i32, char, LinearIndex> = LruRing::with_capacity(2);
+        ring.insert(1, 'a');
+        ring.insert(2, 'b');
+        ring.get(&1);
+        ring.insert(3, 'c');
+        ring.insert(1, 'd');
+        assert_eq!(ring.as_queue().total_pushed(), 4);
+        assert_eq!(ring.as_queue().total_evicted(), 2);
+        assert_eq!(ring.into_queue(), vec![(3, 'c'), (1, 'd')]);
+    }
+}
@@ -0,0 +1,372 @@
+//! Iteration over a range of the queue's contents.
+
+use super::windows::logical_slices;
+use super::*;
+
+#[cfg(all(has_extern_crate_alloc, has_range_bounds))]
+use core::ops::{Bound, RangeBounds};
+#[cfg(all(not(has_extern_crate_alloc), has_range_bounds))]
+use std::ops::{Bound, RangeBounds};
+
+/// Mutable version of `logical_slices()`.
+#[inline]
+fn logical_slices_mut<'a, T>(
+    older: &'a mut [T],
+    newer: &'a mut [T],
+    start: usize,
+    end: usize,
+) -> (&'a mut [T], &'a mut [T]) {
+    let split = older.len();
+    if end <= split {
+        (&mut older[start..end], &mut newer[..0])
+    } else if start >= split {
+        (&mut newer[start - split..end - split], &mut older[..0])
+    } else {
+        (&mut older[start..], &mut newer[..end - split])
+    }
+}
+
+/// An iterator over a range of the queue's contents, going from the oldest items to the newest
+/// ones.
+///
+/// Created with `CircularQueue::range()` and `CircularQueue::oldest_n()`.
+pub struct RangeIter<'a, T: 'a> {
+    older: SliceIter<'a, T>,
+    newer: SliceIter<'a, T>,
+}
+
+impl<'a, T> Iterator for RangeIter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.older.next().or_else(|| self.newer.next())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.older.len() + self.newer.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for RangeIter<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.newer.next_back().or_else(|| self.older.next_back())
+    }
+}
+
+impl<'a, T> ExactSizeIterator for RangeIter<'a, T> {}
+
+impl<'a, T> Clone for RangeIter<'a, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        RangeIter {
+            older: self.older.clone(),
+            newer: self.newer.clone(),
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for RangeIter<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// A mutable iterator over a range of the queue's contents, going from the oldest items to the
+/// newest ones.
+///
+/// Created with `CircularQueue::range_mut()`.
+pub struct RangeIterMut<'a, T: 'a> {
+    older: SliceIterMut<'a, T>,
+    newer: SliceIterMut<'a, T>,
+}
+
+impl<'a, T> Iterator for RangeIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.older.next() {
+            Some(x) => Some(x),
+            None => self.newer.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.older.len() + self.newer.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for RangeIterMut<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self.newer.next_back() {
+            Some(x) => Some(x),
+            None => self.older.next_back(),
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for RangeIterMut<'a, T> {}
+
+impl<'a, T> fmt::Debug for RangeIterMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Showing the items needs `IterMut::as_slice()`, which requires Rust 1.53.
+        f.debug_struct("RangeIterMut")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<T, S: Storage<T>> CircularQueue<T, S> {
+    /// Returns an iterator over the elements at logical indices `start..end`, counting from the
+    /// oldest element.
+    #[inline]
+    pub(crate) fn range_iter<'a>(&'a self, start: usize, end: usize) -> RangeIter<'a, T> {
+        let (newer, older) = self.data.as_slice().split_at(self.insertion_index);
+        let (older, newer) = logical_slices(older, newer, start, end);
+        RangeIter {
+            older: older.iter(),
+            newer: newer.iter(),
+        }
+    }
+
+    /// Returns an iterator over the `n` most recently pushed items, going from the newest one to
+    /// the oldest one.
+    ///
+    /// If the queue holds fewer than `n` items, the iterator yields all of them. Unlike
+    /// `iter().take(n)`, the iterator is double-ended and knows its exact length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(4);
+    /// for x in 1..7 {
+    ///     queue.push(x);
+    /// }
+    ///
+    /// let mut newest = queue.newest_n(2);
+    /// assert_eq!(newest.len(), 2);
+    /// assert_eq!(newest.next(), Some(&6));
+    /// assert_eq!(newest.next(), Some(&5));
+    /// assert_eq!(newest.next(), None);
+    /// ```
+    #[inline]
+    pub fn newest_n<'a>(&'a self, n: usize) -> Rev<RangeIter<'a, T>> {
+        let len = self.len();
+        self.range_iter(len - cmp::min(n, len), len).rev()
+    }
+
+    /// Returns an iterator over the `n` least recently pushed items, going from the oldest one to
+    /// the newest one.
+    ///
+    /// If the queue holds fewer than `n` items, the iterator yields all of them. Unlike
+    /// `asc_iter().take(n)`, the iterator is double-ended and knows its exact length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(4);
+    /// for x in 1..7 {
+    ///     queue.push(x);
+    /// }
+    ///
+    /// let oldest: Vec<_> = queue.oldest_n(2).collect();
+    /// assert_eq!(oldest, [&3, &4]);
+    /// ```
+    #[inline]
+    pub fn oldest_n<'a>(&'a self, n: usize) -> RangeIter<'a, T> {
+        self.range_iter(0, cmp::min(n, self.len()))
+    }
+
+    /// Returns the bounds of `range` of logical indices, panicking if they're out of bounds.
+    #[cfg(has_range_bounds)]
+    fn range_bounds<R: RangeBounds<usize>>(&self, range: R) -> (usize, usize) {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1).expect("range start overflows"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1).expect("range end overflows"),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+
+        assert!(start <= end, "range start is greater than range end");
+        assert!(end <= self.len(), "range end is out of bounds");
+        (start, end)
+    }
+
+    /// Returns an iterator over the elements in `range`, going from the oldest items to the newest
+    /// ones.
+    ///
+    /// The range is made of logical indices counting from the oldest element, like the order of
+    /// `asc_iter()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end or if the end is greater than the
+    /// length of the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(5);
+    /// for x in 1..9 {
+    ///     queue.push(x);
+    /// }
+    ///
+    /// let middle: Vec<_> = queue.range(1..4).collect();
+    /// assert_eq!(middle, [&5, &6, &7]);
+    ///
+    /// let newest_first: Vec<_> = queue.range(3..).rev().collect();
+    /// assert_eq!(newest_first, [&8, &7]);
+    /// ```
+    #[cfg(has_range_bounds)]
+    #[inline]
+    pub fn range<'a, R: RangeBounds<usize>>(&'a self, range: R) -> RangeIter<'a, T> {
+        let (start, end) = self.range_bounds(range);
+        self.range_iter(start, end)
+    }
+
+    /// Returns a mutable iterator over the elements in `range`, going from the oldest items to the
+    /// newest ones.
+    ///
+    /// The range is made of logical indices counting from the oldest element, like the order of
+    /// `asc_iter()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end or if the end is greater than the
+    /// length of the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(3);
+    /// for x in 1..5 {
+    ///     queue.push(x);
+    /// }
+    ///
+    /// for x in queue.range_mut(..2) {
+    ///     *x *= 10;
+    /// }
+    /// assert_eq!(queue, [20, 30, 4]);
+    /// ```
+    #[cfg(has_range_bounds)]
+    #[inline]
+    pub fn range_mut<'a, R: RangeBounds<usize>>(&'a mut self, range: R) -> RangeIterMut<'a, T> {
+        let (start, end) = self.range_bounds(range);
+        let (newer, older) = self.data.as_mut_slice().split_at_mut(self.insertion_index);
+        let (older, newer) = logical_slices_mut(older, newer, start, end);
+        RangeIterMut {
+            older: older.iter_mut(),
+            newer: newer.iter_mut(),
+        }
+    }
+}
+
+#[cfg(all(test, has_alloc))]
+mod tests {
+    use super::*;
+    #[cfg(has_extern_crate_alloc)]
+    use alloc::vec;
+    use tests::wrapped_queues;
+
+    #[test]
+    fn newest_oldest_n() {
+        for q in wrapped_queues(5) {
+            for n in 0..7 {
+                let newest = q.newest_n(n);
+                assert_eq!(newest.len(), cmp::min(n, q.len()));
+                assert!(newest.eq(q.iter().take(n)));
+                let mut expected: Vec<_> = q.iter().take(n).collect();
+                expected.reverse();
+                assert_eq!(q.newest_n(n).rev().collect::<Vec<_>>(), expected);
+
+                let oldest = q.oldest_n(n);
+                assert_eq!(oldest.len(), cmp::min(n, q.len()));
+                assert!(oldest.eq(q.asc_iter().take(n)));
+                let mut expected: Vec<_> = q.asc_iter().take(n).collect();
+                expected.reverse();
+                assert_eq!(q.oldest_n(n).rev().collect::<Vec<_>>(), expected);
+            }
+        }
+    }
+
+    #[cfg(has_range_bounds)]
+    #[test]
+    fn range() {
+        for mut q in wrapped_queues(5) {
+            let len = q.len();
+            for start in 0..len + 1 {
+                for end in start..len + 1 {
+                    let expected: Vec<usize> = q
+                        .asc_iter()
+                        .skip(start)
+                        .take(end - start)
+                        .cloned()
+                        .collect();
+                    assert!(q.range(start..end).eq(expected.iter()));
+                    assert!(q.range(start..end).rev().eq(expected.iter().rev()));
+                    assert_eq!(q.range(start..end).len(), end - start);
+
+                    let actual: Vec<usize> = q.range_mut(start..end).map(|x| *x).collect();
+                    assert_eq!(actual, expected);
+                }
+            }
+
+            assert!(q.range(..).eq(q.asc_iter()));
+            if len > 0 {
+                let bounds = (Bound::Excluded(0), Bound::Included(len - 1));
+                assert!(q.range(bounds).eq(q.asc_iter().skip(1)));
+            }
+        }
+    }
+
+    #[cfg(has_range_bounds)]
+    #[test]
+    fn range_mut() {
+        let mut q = CircularQueue::with_capacity(4);
+        q.extend(vec![1, 2, 3, 4, 5, 6]);
+
+        let mut iter = q.range_mut(1..);
+        *iter.next_back().unwrap() = 60;
+        *iter.next().unwrap() = 40;
+        assert_eq!(iter.len(), 1);
+        assert_eq!(q, [3, 40, 5, 60]);
+    }
+
+    #[cfg(has_range_bounds)]
+    #[test]
+    #[should_panic(expected = "range end is out of bounds")]
+    fn range_out_of_bounds() {
+        let mut q = CircularQueue::with_capacity(4);
+        q.extend(vec![1, 2, 3]);
+        q.range(1..4);
+    }
+
+    #[cfg(has_range_bounds)]
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    fn range_reversed() {
+        let mut q = CircularQueue::with_capacity(4);
+        q.extend(vec![1, 2, 3]);
+        let (start, end) = (2, 1);
+        q.range(start..end);
+    }
+}
@@ -0,0 +1,483 @@
+//! A queue of distinct elements where pushing an element again moves it to the newest position.
+
+use super::*;
+
+#[cfg(all(has_extern_crate_alloc, feature = "std"))]
+use std::collections::hash_map::RandomState;
+#[cfg(not(has_extern_crate_alloc))]
+use std::collections::hash_map::RandomState;
+#[cfg(all(has_extern_crate_alloc, feature = "std"))]
+use std::collections::HashMap;
+#[cfg(not(has_extern_crate_alloc))]
+use std::collections::HashMap;
+#[cfg(any(feature = "std", not(has_extern_crate_alloc)))]
+use std::hash::BuildHasher;
+
+/// Keeps track of the elements of a `RecentSet` to speed up looking them up.
+pub trait RecentSetIndex<T> {
+    /// Records that `x` was added to the set.
+    fn insert(&mut self, x: &T);
+
+    /// Records that `x` was removed from the set.
+    fn remove(&mut self, x: &T);
+
+    /// Returns `false` if `x` is definitely not in the set.
+    fn may_contain(&self, x: &T) -> bool;
+
+    /// Records that all elements were removed from the set.
+    fn clear(&mut self);
+}
+
+/// An index that keeps no information, so that looking up an element always scans the set.
+///
+/// Only requires the elements to implement `Eq`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LinearIndex;
+
+impl<T> RecentSetIndex<T> for LinearIndex {
+    #[inline]
+    fn insert(&mut self, _x: &T) {}
+
+    #[inline]
+    fn remove(&mut self, _x: &T) {}
+
+    #[inline]
+    fn may_contain(&self, _x: &T) -> bool {
+        true
+    }
+
+    #[inline]
+    fn clear(&mut self) {}
+}
+
+/// An index of the hashes of the elements, so that looking up an element that isn't in the set
+/// takes O(1) time.
+///
+/// Elements that are in the set, or share their hash with one, are still found by scanning the
+/// set.
+#[cfg(any(feature = "std", not(has_extern_crate_alloc)))]
+#[derive(Clone, Debug, Default)]
+pub struct HashIndex<S = RandomState> {
+    // The number of elements with each hash.
+    counts: HashMap<u64, usize>,
+    hash_builder: S,
+}
+
+#[cfg(any(feature = "std", not(has_extern_crate_alloc)))]
+impl<S> HashIndex<S> {
+    /// Creates an index hashing elements with `hash_builder`.
+    #[inline]
+    pub fn with_hasher(hash_builder: S) -> Self {
+        HashIndex {
+            counts: HashMap::new(),
+            hash_builder,
+        }
+    }
+}
+
+#[cfg(any(feature = "std", not(has_extern_crate_alloc)))]
+impl<S: BuildHasher> HashIndex<S> {
+    #[inline]
+    fn hash<T: Hash>(&self, x: &T) -> u64 {
+        let mut hasher = self.hash_builder.build_hasher();
+        x.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[cfg(any(feature = "std", not(has_extern_crate_alloc)))]
+impl<T: Hash, S: BuildHasher> RecentSetIndex<T> for HashIndex<S> {
+    #[inline]
+    fn insert(&mut self, x: &T) {
+        let hash = self.hash(x);
+        *self.counts.entry(hash).or_insert(0) += 1;
+    }
+
+    #[inline]
+    fn remove(&mut self, x: &T) {
+        let hash = self.hash(x);
+        let remaining = match self.counts.get_mut(&hash) {
+            Some(count) => {
+                *count -= 1;
+                *count
+            }
+            None => return,
+        };
+        if remaining == 0 {
+            self.counts.remove(&hash);
+        }
+    }
+
+    #[inline]
+    fn may_contain(&self, x: &T) -> bool {
+        self.counts.contains_key(&self.hash(x))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.counts.clear();
+    }
+}
+
+/// A queue of distinct elements, where pushing an element that is already in the queue moves it
+/// to the newest position instead of adding a duplicate.
+///
+/// This keeps the most recently used elements, such as recent files or searches. When the set is
+/// full, pushing a new element evicts the least recently pushed one.
+///
+/// By default, elements are looked up through a `LinearIndex`, which only requires them to
+/// implement `Eq`. With the `std` feature, `HashRecentSet` uses a `HashIndex` instead, which
+/// requires them to implement `Hash`.
+///
+/// # Examples
+///
+/// ```
+/// use circular_queue::RecentSet;
+///
+/// let mut recent: RecentSet<&str> = RecentSet::with_capacity(3);
+/// recent.push("main.rs");
+/// recent.push("lib.rs");
+/// recent.push("main.rs");
+/// assert_eq!(recent.iter().collect::<Vec<_>>(), [&"main.rs", &"lib.rs"]);
+///
+/// recent.push("Cargo.toml");
+/// recent.push("README.md");
+/// assert_eq!(
+///     recent.iter().collect::<Vec<_>>(),
+///     [&"README.md", &"Cargo.toml", &"main.rs"]
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct RecentSet<T, X = LinearIndex> {
+    pub(crate) queue: CircularQueue<T>,
+    index: X,
+}
+
+/// A `RecentSet` looking up elements through a `HashIndex`.
+///
+/// # Examples
+///
+/// ```
+/// use circular_queue::HashRecentSet;
+///
+/// let mut recent: HashRecentSet<u32> = HashRecentSet::with_capacity(2);
+/// recent.push(1);
+/// recent.push(2);
+/// assert_eq!(recent.push(1), Some(1));
+/// assert_eq!(recent.iter().collect::<Vec<_>>(), [&1, &2]);
+/// ```
+#[cfg(any(feature = "std", not(has_extern_crate_alloc)))]
+pub type HashRecentSet<T, S = RandomState> = RecentSet<T, HashIndex<S>>;
+
+impl<T: Eq, X: RecentSetIndex<T>> RecentSet<T, X> {
+    /// Constructs a new, empty `RecentSet` with the requested capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self
+    where
+        X: Default,
+    {
+        Self::with_capacity_and_index(capacity, X::default())
+    }
+
+    /// Constructs a new, empty `RecentSet` with the requested capacity and index.
+    #[inline]
+    pub fn with_capacity_and_index(capacity: usize, index: X) -> Self {
+        RecentSet {
+            queue: CircularQueue::with_capacity(capacity),
+            index,
+        }
+    }
+
+    /// Returns the number of elements in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if the set contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Returns the capacity of the set.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+
+    /// Returns the underlying queue.
+    #[inline]
+    pub fn as_queue(&self) -> &CircularQueue<T> {
+        &self.queue
+    }
+
+    /// Converts the set into the underlying queue.
+    #[inline]
+    pub fn into_queue(self) -> CircularQueue<T> {
+        self.queue
+    }
+
+    /// Returns the index of the element equal to `x` counting from the oldest one.
+    #[inline]
+    fn position(&self, x: &T) -> Option<usize> {
+        if !self.index.may_contain(x) {
+            return None;
+        }
+
+        // Recently used elements are the likeliest to be used again.
+        self.queue
+            .position_from_newest(|y| y == x)
+            .map(|i| self.len() - 1 - i)
+    }
+
+    /// Returns `true` if the set contains an element equal to `x`.
+    #[inline]
+    pub fn contains(&self, x: &T) -> bool {
+        self.position(x).is_some()
+    }
+
+    /// Pushes `x` into the set, making it the newest element.
+    ///
+    /// If an equal element is already in the set, it's moved to the newest position, replaced with
+    /// `x` and returned. Otherwise, if the set is full, the oldest element is evicted and
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::{LinearIndex, RecentSet};
+    ///
+    /// let mut searches: RecentSet<String, LinearIndex> = RecentSet::with_capacity(2);
+    /// assert_eq!(searches.push("rust".to_owned()), None);
+    /// assert_eq!(searches.push("serde".to_owned()), None);
+    ///
+    /// // Searching again moves it to the front.
+    /// assert_eq!(searches.push("rust".to_owned()), Some("rust".to_owned()));
+    ///
+    /// // The least recent search is evicted.
+    /// assert_eq!(searches.push("tokio".to_owned()), Some("serde".to_owned()));
+    /// assert_eq!(searches.iter().collect::<Vec<_>>(), ["tokio", "rust"]);
+    /// ```
+    pub fn push(&mut self, x: T) -> Popped<T> {
+        if self.capacity() == 0 {
+            self.queue.evictions.pushed(1);
+            self.queue.evictions.evict(&x);
+            return Some(x);
+        }
+
+        if let Some(index) = self.position(&x) {
+            self.queue.move_to_newest(index);
+            let old = replace(self.queue.newest_mut().unwrap(), x);
+            self.queue.evictions.pushed(1);
+            self.queue.evictions.evict(&old);
+            return Some(old);
+        }
+
+        self.index.insert(&x);
+        let old = self.queue.push(x);
+        if let Some(ref old) = old {
+            self.index.remove(old);
+        }
+        old
+    }
+
+    /// Removes the element equal to `x` from the set and returns it.
+    ///
+    /// The remaining elements keep their order.
+    pub fn remove(&mut self, x: &T) -> Option<T> {
+        match self.position(x) {
+            Some(index) => {
+                let removed = self.queue.remove_at(index);
+                self.index.remove(&removed);
+                Some(removed)
+            }
+            None => None,
+        }
+    }
+
+    /// Removes all elements from the set.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.queue.clear();
+        self.index.clear();
+    }
+
+    /// Returns an iterator over the set's contents, going from the most recently pushed elements
+    /// to the oldest ones.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> Iter<'a, T> {
+        self.queue.iter()
+    }
+
+    /// Returns an iterator over the set's contents, going from the oldest elements to the most
+    /// recently pushed ones.
+    #[inline]
+    pub fn asc_iter<'a>(&'a self) -> AscIter<'a, T> {
+        self.queue.asc_iter()
+    }
+}
+
+impl<T> CircularQueue<T> {
+    /// Moves the element at `index` counting from the oldest one to the newest position, shifting
+    /// the newer elements by one.
+    pub(crate) fn move_to_newest(&mut self, index: usize) {
+        let len = self.len();
+        let data = self.data.as_mut_slice();
+        for i in index..len - 1 {
+            data.swap(
+                (self.insertion_index + i) % len,
+                (self.insertion_index + i + 1) % len,
+            );
+        }
+    }
+
+    /// Removes the element at `index` counting from the oldest one. The remaining elements keep
+    /// their order.
+    pub(crate) fn remove_at(&mut self, index: usize) -> T {
+        self.move_to_newest(index);
+        self.make_contiguous();
+        let removed = self.data.pop().unwrap();
+        self.insertion_index = self.len();
+        removed
+    }
+}
+
+impl<T, X, U> PartialEq<[U]> for RecentSet<T, X>
+where
+    T: PartialEq<U>,
+{
+    /// Compares the elements going from the oldest ones to the newest ones, like `asc_iter()`.
+    #[inline]
+    fn eq(&self, other: &[U]) -> bool {
+        self.queue == *other
+    }
+}
+
+impl<'a, T, X, U> PartialEq<&'a [U]> for RecentSet<T, X>
+where
+    T: PartialEq<U>,
+{
+    /// Compares the elements going from the oldest ones to the newest ones, like `asc_iter()`.
+    #[inline]
+    fn eq(&self, other: &&'a [U]) -> bool {
+        self.queue == *other
+    }
+}
+
+#[cfg(all(test, has_alloc))]
+mod tests {
+    use super::*;
+    #[cfg(has_extern_crate_alloc)]
+    use alloc::vec;
+
+    fn check<X: RecentSetIndex<i32> + Default + fmt::Debug>() {
+        let mut set: RecentSet<i32, X> = RecentSet::with_capacity(4);
+        for &x in &[1, 2, 3, 2, 1, 4] {
+            let expected = if set.contains(&x) { Some(x) } else { None };
+            assert_eq!(set.push(x), expected);
+        }
+        assert_eq!(set, &[3, 2, 1, 4][..]);
+
+        assert_eq!(set.push(5), Some(3));
+        assert_eq!(set.push(2), Some(2));
+        assert_eq!(set, &[1, 4, 5, 2][..]);
+        assert!(!set.contains(&3));
+        assert!(set.contains(&4));
+
+        assert_eq!(set.remove(&4), Some(4));
+        assert_eq!(set.remove(&4), None);
+        assert_eq!(set, &[1, 5, 2][..]);
+
+        assert_eq!(set.push(6), None);
+        assert_eq!(set.push(7), Some(1));
+        assert_eq!(set.push(5), Some(5));
+        assert_eq!(set, &[2, 6, 7, 5][..]);
+        assert_eq!(set.iter().cloned().collect::<Vec<_>>(), [5, 7, 6, 2]);
+
+        set.clear();
+        assert!(set.is_empty());
+        assert!(!set.contains(&5));
+        assert_eq!(set.push(5), None);
+
+        let mut set: RecentSet<i32, X> = RecentSet::with_capacity(0);
+        assert_eq!(set.push(1), Some(1));
+        assert!(!set.contains(&1));
+        assert_eq!(set.as_queue().total_pushed(), 1);
+        assert_eq!(set.as_queue().total_evicted(), 1);
+    }
+
+    #[test]
+    fn linear() {
+        check::<LinearIndex>();
+    }
+
+    #[cfg(any(feature = "std", not(has_extern_crate_alloc)))]
+    #[test]
+    fn hashed() {
+        check::<HashIndex>();
+    }
+
+    #[cfg(any(feature = "std", not(has_extern_crate_alloc)))]
+    #[test]
+    fn hash_collisions() {
+        use std::hash::{BuildHasherDefault, Hasher};
+
+        #[derive(Default)]
+        struct Constant;
+
+        impl Hasher for Constant {
+            fn finish(&self) -> u64 {
+                0
+            }
+
+            fn write(&mut self, _bytes: &[u8]) {}
+        }
+
+        check::<HashIndex<BuildHasherDefault<Constant>>>();
+    }
+
+    #[test]
+    fn every_position() {
+        for len in 1..6 {
+            for offset in 0..5 {
+                for moved in 0..len {
+                    let mut set: RecentSet<usize, LinearIndex> = RecentSet::with_capacity(5);
+                    for x in 100..100 + offset {
+                        set.push(x);
+                    }
+                    for x in 0..len {
+                        set.push(x);
+                    }
+
+                    let mut expected: Vec<usize> = set.asc_iter().cloned().collect();
+                    let x = expected.remove(expected.len() - len + moved);
+                    expected.push(x);
+
+                    assert_eq!(set.push(moved), Some(moved));
+                    assert_eq!(set, &expected[..]);
+
+                    expected.retain(|&y| y != x);
+                    assert_eq!(set.remove(&moved), Some(moved));
+                    assert_eq!(set, &expected[..]);
+
+                    set.push(200);
+                    expected.push(200);
+                    assert_eq!(set, &expected[..]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn counters() {
+        let mut set: RecentSet<i32, LinearIndex> = RecentSet::with_capacity(2);
+        set.push(1);
+        set.push(2);
+        set.push(1);
+        set.push(3);
+        assert_eq!(set.as_queue().total_pushed(), 4);
+        assert_eq!(set.as_queue().total_evicted(), 2);
+        assert_eq!(set.into_queue(), vec![1, 3]);
+    }
+}
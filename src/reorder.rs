@@ -0,0 +1,464 @@
+//! Reordering the queue's contents in place.
+
+use super::*;
+
+/// Rotates `slice` to the left by `mid` elements.
+// `slice::rotate_left()` requires Rust 1.26.
+#[inline]
+fn rotate_slice_left<T>(slice: &mut [T], mid: usize) {
+    {
+        let (a, b) = slice.split_at_mut(mid);
+        a.reverse();
+        b.reverse();
+    }
+    slice.reverse();
+}
+
+impl<T, S: Storage<T>> CircularQueue<T, S> {
+    /// Rearranges the elements to go from the oldest to the newest in the storage and returns
+    /// them as one slice.
+    #[inline]
+    pub(crate) fn make_contiguous(&mut self) -> &mut [T] {
+        self.normalize();
+        self.insertion_index = if self.is_full() { 0 } else { self.len() };
+        self.data.as_mut_slice()
+    }
+
+    /// Rotates the queue's contents `mid` places to the left, so that the element that was
+    /// `mid` places after the oldest one becomes the oldest.
+    ///
+    /// This is O(1) when the queue is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid` is greater than the length of the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(5);
+    /// queue.extend(vec![1, 2, 3, 4, 5]);
+    ///
+    /// queue.rotate_left(2);
+    /// assert_eq!(queue, [3, 4, 5, 1, 2]);
+    /// assert_eq!(queue.push(6), Some(3));
+    /// ```
+    #[inline]
+    pub fn rotate_left(&mut self, mid: usize) {
+        let len = self.len();
+        assert!(mid <= len, "rotation is greater than the queue length");
+
+        if mid == len {
+            return;
+        }
+
+        if self.is_full() {
+            self.insertion_index = (self.insertion_index + mid) % len;
+        } else {
+            rotate_slice_left(&mut self.data.as_mut_slice()[..len], mid);
+        }
+    }
+
+    /// Rotates the queue's contents `k` places to the right, so that the element that was `k`
+    /// places before the newest one becomes the newest.
+    ///
+    /// This is O(1) when the queue is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is greater than the length of the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(5);
+    /// queue.extend(vec![1, 2, 3, 4]);
+    ///
+    /// queue.rotate_right(1);
+    /// assert_eq!(queue, [4, 1, 2, 3]);
+    /// ```
+    #[inline]
+    pub fn rotate_right(&mut self, k: usize) {
+        let len = self.len();
+        assert!(k <= len, "rotation is greater than the queue length");
+        self.rotate_left(len - k);
+    }
+
+    /// Reverses the order of the queue's contents, so that the newest element becomes the oldest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(3);
+    /// queue.extend(vec![1, 2, 3, 4]);
+    ///
+    /// queue.reverse();
+    /// assert_eq!(queue, [4, 3, 2]);
+    /// ```
+    #[inline]
+    pub fn reverse(&mut self) {
+        self.make_contiguous().reverse();
+    }
+
+    /// Sorts the queue's contents, so that the smallest element becomes the oldest.
+    ///
+    /// The sort is stable. Afterwards, the elements go from the oldest to the newest in the
+    /// storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(4);
+    /// queue.extend(vec![9, 3, 7, 1, 5]);
+    ///
+    /// queue.sort();
+    /// assert_eq!(queue, [1, 3, 5, 7]);
+    ///
+    /// // The smallest element is overwritten first.
+    /// queue.push(2);
+    /// assert_eq!(queue, [3, 5, 7, 2]);
+    /// ```
+    #[cfg(has_alloc)]
+    #[inline]
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.make_contiguous().sort();
+    }
+
+    /// Sorts the queue's contents with a comparator function, so that the smallest element
+    /// becomes the oldest.
+    ///
+    /// The sort is stable. Afterwards, the elements go from the oldest to the newest in the
+    /// storage.
+    #[cfg(has_alloc)]
+    #[inline]
+    pub fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.make_contiguous().sort_by(compare);
+    }
+
+    /// Sorts the queue's contents with a key extraction function, so that the element with the
+    /// smallest key becomes the oldest.
+    ///
+    /// The sort is stable. Afterwards, the elements go from the oldest to the newest in the
+    /// storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(3);
+    /// queue.extend(vec![(3, 'a'), (1, 'b'), (3, 'c')]);
+    ///
+    /// queue.sort_by_key(|&(priority, _)| priority);
+    /// assert_eq!(queue, [(1, 'b'), (3, 'a'), (3, 'c')]);
+    /// ```
+    #[cfg(has_alloc)]
+    #[inline]
+    pub fn sort_by_key<K, F>(&mut self, f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.make_contiguous().sort_by_key(f);
+    }
+
+    /// Sorts the queue's contents, so that the smallest element becomes the oldest.
+    ///
+    /// The sort is unstable but doesn't allocate. Afterwards, the elements go from the oldest to
+    /// the newest in the storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(4);
+    /// queue.extend(vec![9, 3, 7, 1, 5]);
+    ///
+    /// queue.sort_unstable();
+    /// assert_eq!(queue, [1, 3, 5, 7]);
+    /// ```
+    #[cfg(has_sort_unstable)]
+    #[inline]
+    pub fn sort_unstable(&mut self)
+    where
+        T: Ord,
+    {
+        self.make_contiguous().sort_unstable();
+    }
+
+    /// Sorts the queue's contents with a comparator function, so that the smallest element
+    /// becomes the oldest.
+    ///
+    /// The sort is unstable but doesn't allocate. Afterwards, the elements go from the oldest to
+    /// the newest in the storage.
+    #[cfg(has_sort_unstable)]
+    #[inline]
+    pub fn sort_unstable_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.make_contiguous().sort_unstable_by(compare);
+    }
+
+    /// Removes consecutive elements for which `same_bucket` returns `true`, keeping the oldest
+    /// one of each run.
+    ///
+    /// `same_bucket` is called with each element and the last element that was kept before it, in
+    /// ascending order. The removed elements are reported as evicted from the oldest to the
+    /// newest. Afterwards, the elements go from the oldest to the newest in the storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(5);
+    /// queue.extend(vec!["a", "A", "b", "c", "C"]);
+    ///
+    /// queue.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    /// assert_eq!(queue, ["a", "b", "c"]);
+    /// ```
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let len = self.len();
+        if len < 2 {
+            return;
+        }
+
+        self.make_contiguous();
+        let kept = {
+            let slice = self.data.as_mut_slice();
+            let evictions = &mut self.evictions;
+
+            // Move the kept elements to the front, keeping their order. The removed ones end up
+            // shuffled at the back, so they're reported as soon as they're found.
+            let mut kept = 1;
+            for i in 1..len {
+                let same = {
+                    let (front, back) = slice.split_at_mut(i);
+                    same_bucket(&mut back[0], &mut front[kept - 1])
+                };
+                if same {
+                    evictions.evict(&slice[i]);
+                } else {
+                    slice.swap(kept, i);
+                    kept += 1;
+                }
+            }
+            kept
+        };
+
+        if kept == len {
+            return;
+        }
+
+        self.data.truncate(kept);
+        self.insertion_index = kept;
+    }
+
+    /// Removes consecutive elements that resolve to the same key, keeping the oldest one of each
+    /// run.
+    ///
+    /// The removed elements are reported as evicted. Afterwards, the elements go from the oldest
+    /// to the newest in the storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut readings = CircularQueue::with_capacity(6);
+    /// readings.extend(vec![(1, 20), (2, 20), (3, 21), (4, 21), (5, 20)]);
+    ///
+    /// // Only keep the readings where the value changed.
+    /// readings.dedup_by_key(|&mut (_, value)| value);
+    /// assert_eq!(readings, [(1, 20), (3, 21), (5, 20)]);
+    /// ```
+    #[inline]
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        K: PartialEq,
+        F: FnMut(&mut T) -> K,
+    {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+
+    /// Removes consecutive equal elements, keeping the oldest one of each run.
+    ///
+    /// The removed elements are reported as evicted. Afterwards, the elements go from the oldest
+    /// to the newest in the storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(4);
+    /// queue.extend(vec![1, 1, 2, 2, 2, 1]);
+    ///
+    /// queue.dedup();
+    /// assert_eq!(queue, [2, 1]);
+    /// ```
+    #[inline]
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b)
+    }
+}
+
+#[cfg(all(test, has_alloc))]
+mod tests {
+    use super::*;
+    #[cfg(has_extern_crate_alloc)]
+    use alloc::vec;
+    use tests::wrapped_queues;
+
+    fn asc(q: &CircularQueue<usize>) -> Vec<usize> {
+        q.asc_iter().cloned().collect()
+    }
+
+    // Pushing into the queue behaves as if it was built from `asc(q)`.
+    fn check_push(mut q: CircularQueue<usize>) {
+        let mut expected = CircularQueue::with_capacity(q.capacity());
+        expected.extend(asc(&q));
+
+        for x in 100..100 + q.capacity() + 1 {
+            assert_eq!(q.push(x), expected.push(x));
+            assert_eq!(q, expected);
+        }
+    }
+
+    #[test]
+    fn rotate() {
+        for q in wrapped_queues(5) {
+            let len = q.len();
+            for mid in 0..len + 1 {
+                let mut expected = asc(&q);
+                expected.extend_from_slice(&asc(&q)[..mid]);
+                expected.drain(..mid);
+
+                let mut left = q.clone();
+                left.rotate_left(mid);
+                assert_eq!(left, expected);
+                check_push(left);
+
+                let mut right = q.clone();
+                right.rotate_right(len - mid);
+                assert_eq!(right, expected);
+                check_push(right);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "rotation is greater than the queue length")]
+    fn rotate_too_far() {
+        let mut q = CircularQueue::with_capacity(4);
+        q.extend(vec![1, 2, 3]);
+        q.rotate_left(4);
+    }
+
+    #[test]
+    fn reverse() {
+        for q in wrapped_queues(5) {
+            let mut expected = asc(&q);
+            expected.reverse();
+
+            let mut q = q.clone();
+            q.reverse();
+            assert_eq!(q, expected);
+            check_push(q);
+        }
+    }
+
+    #[test]
+    fn sort() {
+        for q in wrapped_queues(5) {
+            let mut q = q.clone();
+            q.reverse();
+            q.sort();
+            assert_eq!(q, (0..q.len()).collect::<Vec<_>>());
+            check_push(q.clone());
+
+            q.sort_by(|a, b| b.cmp(a));
+            assert_eq!(q, (0..q.len()).rev().collect::<Vec<_>>());
+            check_push(q.clone());
+
+            q.sort_by_key(|&x| x % 2);
+            let mut expected = asc(&q);
+            expected.sort_by_key(|&x| x % 2);
+            assert_eq!(q, expected);
+            check_push(q);
+        }
+    }
+
+    #[cfg(has_sort_unstable)]
+    #[test]
+    fn sort_unstable() {
+        for q in wrapped_queues(5) {
+            let mut q = q.clone();
+            q.reverse();
+            q.sort_unstable();
+            assert_eq!(q, (0..q.len()).collect::<Vec<_>>());
+            check_push(q.clone());
+
+            q.sort_unstable_by(|a, b| b.cmp(a));
+            assert_eq!(q, (0..q.len()).rev().collect::<Vec<_>>());
+            check_push(q);
+        }
+    }
+
+    #[test]
+    fn dedup() {
+        let mut q = CircularQueue::with_capacity(6);
+        q.extend(vec![9, 9, 1, 1, 2, 3, 3, 3]);
+        q.dedup();
+        assert_eq!(q, [1, 2, 3]);
+        assert_eq!(q.total_evicted(), 5);
+
+        q.extend(vec![4, 5, 6, 7]);
+        assert_eq!(q, [2, 3, 4, 5, 6, 7]);
+
+        let mut q = CircularQueue::with_capacity(4);
+        q.extend(vec![1, 2, 3]);
+        q.dedup();
+        assert_eq!(q, [1, 2, 3]);
+        assert_eq!(q.total_evicted(), 0);
+    }
+
+    #[test]
+    fn dedup_by_key_reports_evictions() {
+        use std::sync::{Arc, Mutex};
+
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let mut q = CircularQueue::with_capacity(6);
+        {
+            let evicted = evicted.clone();
+            q.set_on_evict(move |&x| evicted.lock().unwrap().push(x));
+        }
+
+        q.extend(vec![10, 11, 12, 20, 30, 31]);
+        q.dedup_by_key(|x| *x / 10);
+        assert_eq!(q, [10, 20, 30]);
+        assert_eq!(*evicted.lock().unwrap(), [11, 12, 31]);
+    }
+}
@@ -0,0 +1,178 @@
+//! Searching the queue's contents.
+//!
+//! The searches run over the two halves of the storage as plain slices rather than through the
+//! chained iterators, so that they optimize like searches over a single slice.
+
+use super::*;
+
+impl<T, S: Storage<T>> CircularQueue<T, S> {
+    /// Returns `true` if the queue contains an element equal to `x`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(3);
+    /// queue.extend(vec![1, 2, 3, 4]);
+    ///
+    /// assert!(queue.contains(&4));
+    /// assert!(!queue.contains(&1));
+    /// ```
+    #[inline]
+    pub fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        let (a, b) = self.data.as_slice().split_at(self.insertion_index);
+        a.contains(x) || b.contains(x)
+    }
+
+    /// Searches for an element matching `predicate` starting from the oldest one, and returns its
+    /// index counting from the oldest element.
+    ///
+    /// The index can be passed to `get()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(4);
+    /// queue.extend(vec![1, 2, 3, 4, 5, 6]);
+    ///
+    /// assert_eq!(queue.position_from_oldest(|&x| x % 2 == 0), Some(1));
+    /// assert_eq!(queue.position_from_oldest(|&x| x > 10), None);
+    /// ```
+    #[inline]
+    pub fn position_from_oldest<P>(&self, mut predicate: P) -> Option<usize>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let (newer, older) = self.data.as_slice().split_at(self.insertion_index);
+
+        if let Some(i) = older.iter().position(&mut predicate) {
+            return Some(i);
+        }
+        newer
+            .iter()
+            .position(&mut predicate)
+            .map(|i| older.len() + i)
+    }
+
+    /// Searches for an element matching `predicate` starting from the newest one, and returns its
+    /// index counting from the newest element.
+    ///
+    /// This is the same as `iter().position(predicate)`. The element can be retrieved with
+    /// `get(len() - 1 - index)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(4);
+    /// queue.extend(vec![1, 2, 3, 4, 5, 6]);
+    ///
+    /// assert_eq!(queue.position_from_newest(|&x| x % 2 == 1), Some(1));
+    /// assert_eq!(queue.position_from_newest(|&x| x > 10), None);
+    /// ```
+    #[inline]
+    pub fn position_from_newest<P>(&self, mut predicate: P) -> Option<usize>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let (newer, older) = self.data.as_slice().split_at(self.insertion_index);
+
+        if let Some(i) = newer.iter().rposition(&mut predicate) {
+            return Some(newer.len() - 1 - i);
+        }
+        older
+            .iter()
+            .rposition(&mut predicate)
+            .map(|i| self.len() - 1 - i)
+    }
+
+    /// Returns the most recently pushed element matching `predicate` along with its index
+    /// counting from the oldest element.
+    ///
+    /// The index can be passed to `get()` or `get_mut()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut events = CircularQueue::with_capacity(4);
+    /// events.extend(vec![("open", 1), ("read", 2), ("open", 3), ("close", 4)]);
+    ///
+    /// let (index, event) = events.find_newest(|&(kind, _)| kind == "open").unwrap();
+    /// assert_eq!(*event, ("open", 3));
+    /// assert_eq!(index, 2);
+    ///
+    /// events.get_mut(index).unwrap().0 = "reopen";
+    /// assert_eq!(events.get(index), Some(&("reopen", 3)));
+    /// ```
+    #[inline]
+    pub fn find_newest<P>(&self, predicate: P) -> Option<(usize, &T)>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        match self.position_from_newest(predicate) {
+            Some(i) => {
+                let index = self.len() - 1 - i;
+                Some((index, self.get(index).unwrap()))
+            }
+            None => None,
+        }
+    }
+}
+
+#[cfg(all(test, has_alloc))]
+mod tests {
+    use super::*;
+    use tests::wrapped_queues;
+
+    #[test]
+    fn get() {
+        for mut q in wrapped_queues(5) {
+            let asc: Vec<usize> = q.asc_iter().cloned().collect();
+            for (i, x) in asc.iter().enumerate() {
+                assert_eq!(q.get(i), Some(x));
+                *q.get_mut(i).unwrap() += 10;
+            }
+            assert_eq!(q.get(asc.len()), None);
+            assert_eq!(q.get_mut(asc.len()), None);
+            let expected: Vec<usize> = asc.iter().map(|x| x + 10).collect();
+            assert_eq!(q, expected);
+        }
+    }
+
+    #[test]
+    fn contains() {
+        for q in wrapped_queues(5) {
+            for x in 0..6 {
+                assert_eq!(q.contains(&x), x < q.len());
+            }
+        }
+    }
+
+    #[test]
+    fn position() {
+        for q in wrapped_queues(5) {
+            for m in 1..4 {
+                let pred = |x: &usize| x % m == m - 1;
+                assert_eq!(q.position_from_oldest(pred), q.asc_iter().position(pred));
+                assert_eq!(q.position_from_newest(pred), q.iter().position(pred));
+
+                match q.find_newest(pred) {
+                    Some((index, x)) => {
+                        assert_eq!(q.get(index), Some(x));
+                        assert_eq!(Some(x), q.iter().find(|x| pred(x)));
+                    }
+                    None => assert!(q.iter().all(|x| !pred(x))),
+                }
+            }
+        }
+    }
+}
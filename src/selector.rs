@@ -0,0 +1,254 @@
+//! Choosing which element to evict when pushing into a full queue.
+
+use super::*;
+
+/// Chooses the element to evict when pushing into a full queue with
+/// `CircularQueue::push_evicting()`.
+///
+/// Implemented by `EvictOldest`, `EvictLowestByKey` and `EvictWith`.
+pub trait EvictionSelector<T> {
+    /// Returns the index of the element to evict among `items`, counting from the oldest one.
+    ///
+    /// `items` goes from the oldest elements of the full queue to the newest ones, and `new` is
+    /// the element being pushed. Returning `items.len()` evicts `new` itself, so it isn't pushed.
+    fn select<'a>(&mut self, items: RangeIter<'a, T>, new: &'a T) -> usize;
+}
+
+/// Evicts the oldest element, like `push()` does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EvictOldest;
+
+impl<T> EvictionSelector<T> for EvictOldest {
+    #[inline]
+    fn select<'a>(&mut self, _items: RangeIter<'a, T>, _new: &'a T) -> usize {
+        0
+    }
+}
+
+/// Evicts the element with the lowest key, including the element being pushed.
+///
+/// Among elements with equal keys, the oldest one is evicted, so the element being pushed is
+/// evicted only if its key is lower than all others.
+#[derive(Clone, Copy, Debug)]
+pub struct EvictLowestByKey<F> {
+    key: F,
+}
+
+impl<F> EvictLowestByKey<F> {
+    /// Creates a selector evicting the element for which `key` returns the lowest value.
+    #[inline]
+    pub fn new(key: F) -> Self {
+        EvictLowestByKey { key }
+    }
+}
+
+impl<T, K, F> EvictionSelector<T> for EvictLowestByKey<F>
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    fn select<'a>(&mut self, items: RangeIter<'a, T>, new: &'a T) -> usize {
+        let mut lowest = items.len();
+        let mut lowest_key = (self.key)(new);
+
+        for (i, x) in items.enumerate() {
+            let key = (self.key)(x);
+            if key <= lowest_key && (key < lowest_key || i < lowest) {
+                lowest = i;
+                lowest_key = key;
+            }
+        }
+
+        lowest
+    }
+}
+
+/// Evicts the element chosen by a closure.
+///
+/// The closure is called with the same arguments as `EvictionSelector::select()`.
+#[derive(Clone, Copy, Debug)]
+pub struct EvictWith<F> {
+    select: F,
+}
+
+impl<F> EvictWith<F> {
+    /// Creates a selector evicting the element at the index returned by `select`.
+    #[inline]
+    pub fn new(select: F) -> Self {
+        EvictWith { select }
+    }
+}
+
+impl<T, F> EvictionSelector<T> for EvictWith<F>
+where
+    F: for<'a> FnMut(RangeIter<'a, T>, &'a T) -> usize,
+{
+    #[inline]
+    fn select<'a>(&mut self, items: RangeIter<'a, T>, new: &'a T) -> usize {
+        (self.select)(items, new)
+    }
+}
+
+impl<T, S: Storage<T>> CircularQueue<T, S> {
+    /// Pushes a new element into the queue, letting `selector` choose the element to evict if the
+    /// queue is full.
+    ///
+    /// This is like `push()` with `OverflowPolicy::Overwrite`, except that the evicted element
+    /// isn't necessarily the oldest one. The remaining elements keep their order, so `iter()` and
+    /// `asc_iter()` still go in the order the elements were pushed in, with the new element being
+    /// the newest one. If `selector` chooses the new element, it's returned without being pushed.
+    ///
+    /// With `OverflowPolicy::Reject`, this behaves like `push()` and `selector` isn't called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `selector` returns an index greater than the length of the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::{CircularQueue, EvictLowestByKey};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Alert {
+    ///     severity: u8,
+    ///     message: &'static str,
+    /// }
+    ///
+    /// let alert = |severity, message| Alert { severity, message };
+    /// let mut by_severity = EvictLowestByKey::new(|a: &Alert| a.severity);
+    ///
+    /// let mut alerts = CircularQueue::with_capacity(3);
+    /// alerts.push(alert(5, "disk failing"));
+    /// alerts.push(alert(1, "high latency"));
+    /// alerts.push(alert(3, "retrying"));
+    ///
+    /// // The least severe alert is dropped instead of the oldest one.
+    /// let evicted = alerts.push_evicting(alert(4, "overheating"), &mut by_severity);
+    /// assert_eq!(evicted, Some(alert(1, "high latency")));
+    ///
+    /// // A new alert less severe than all others isn't pushed.
+    /// let evicted = alerts.push_evicting(alert(2, "slow query"), &mut by_severity);
+    /// assert_eq!(evicted, Some(alert(2, "slow query")));
+    ///
+    /// let messages: Vec<_> = alerts.asc_iter().map(|a| a.message).collect();
+    /// assert_eq!(messages, ["disk failing", "retrying", "overheating"]);
+    /// ```
+    pub fn push_evicting<E>(&mut self, x: T, selector: &mut E) -> Popped<T>
+    where
+        E: EvictionSelector<T> + ?Sized,
+    {
+        if !self.is_full() || self.is_empty() || self.overflow_policy == OverflowPolicy::Reject {
+            return self.push(x);
+        }
+
+        let len = self.len();
+        let victim = selector.select(self.range_iter(0, len), &x);
+        assert!(victim <= len, "selected index is out of bounds");
+
+        if victim == len {
+            self.evictions.pushed(1);
+            self.evictions.evict(&x);
+            return Some(x);
+        }
+
+        // Move the victim to the oldest position, shifting the elements older than it by one.
+        let data = self.data.as_mut_slice();
+        for i in (0..victim).rev() {
+            data.swap(
+                (self.insertion_index + i) % len,
+                (self.insertion_index + i + 1) % len,
+            );
+        }
+
+        self.push(x)
+    }
+}
+
+#[cfg(all(test, has_alloc))]
+mod tests {
+    use super::*;
+    #[cfg(has_extern_crate_alloc)]
+    use alloc::vec;
+
+    #[test]
+    fn oldest() {
+        let mut q = CircularQueue::with_capacity(3);
+        let mut expected = CircularQueue::with_capacity(3);
+        for x in 0..10 {
+            assert_eq!(q.push_evicting(x, &mut EvictOldest), expected.push(x));
+            assert_eq!(q, expected);
+        }
+        assert_eq!(q.total_evicted(), 7);
+    }
+
+    #[test]
+    fn lowest_by_key() {
+        let mut q = CircularQueue::with_capacity(4);
+        let mut lowest = EvictLowestByKey::new(|&(priority, _): &(u8, char)| priority);
+
+        for &x in &[(2, 'a'), (1, 'b'), (3, 'c'), (1, 'd')] {
+            assert_eq!(q.push_evicting(x, &mut lowest), None);
+        }
+
+        // The oldest of the lowest is evicted.
+        assert_eq!(q.push_evicting((2, 'e'), &mut lowest), Some((1, 'b')));
+        assert_eq!(q, [(2, 'a'), (3, 'c'), (1, 'd'), (2, 'e')]);
+        assert_eq!(q.push_evicting((2, 'f'), &mut lowest), Some((1, 'd')));
+        assert_eq!(q, [(2, 'a'), (3, 'c'), (2, 'e'), (2, 'f')]);
+
+        // A new element with an equal lowest key evicts an existing one.
+        assert_eq!(q.push_evicting((2, 'g'), &mut lowest), Some((2, 'a')));
+        assert_eq!(q.push_evicting((1, 'h'), &mut lowest), Some((1, 'h')));
+        assert_eq!(q, [(3, 'c'), (2, 'e'), (2, 'f'), (2, 'g')]);
+
+        assert_eq!(q.total_pushed(), 8);
+        assert_eq!(q.total_evicted(), 4);
+
+        // Pushing keeps working as usual.
+        q.push((0, 'i'));
+        assert_eq!(q, [(2, 'e'), (2, 'f'), (2, 'g'), (0, 'i')]);
+    }
+
+    #[test]
+    fn closure() {
+        for victim in 0..5 {
+            let mut q = CircularQueue::with_capacity(4);
+            q.extend(vec![0, 1, 2, 3, 4, 5]);
+
+            let mut selector = EvictWith::new(|items: RangeIter<i32>, new: &i32| {
+                assert_eq!(items.cloned().collect::<Vec<_>>(), [2, 3, 4, 5]);
+                assert_eq!(*new, 6);
+                victim
+            });
+            let evicted = q.push_evicting(6, &mut selector);
+
+            let mut expected = vec![2, 3, 4, 5, 6];
+            assert_eq!(evicted, Some(expected.remove(victim)));
+            assert_eq!(q, expected);
+        }
+    }
+
+    #[test]
+    fn not_full_or_reject() {
+        let mut q = CircularQueue::with_capacity(2);
+        let mut panic = EvictWith::new(|_: RangeIter<i32>, _: &i32| -> usize { panic!() });
+        assert_eq!(q.push_evicting(1, &mut panic), None);
+        assert_eq!(q.push_evicting(2, &mut panic), None);
+
+        q.set_overflow_policy(OverflowPolicy::Reject);
+        assert_eq!(q.push_evicting(3, &mut panic), Some(3));
+        assert_eq!(q, [1, 2]);
+
+        let mut q = CircularQueue::with_capacity(0);
+        assert_eq!(q.push_evicting(1, &mut panic), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "selected index is out of bounds")]
+    fn out_of_bounds() {
+        let mut q = CircularQueue::with_capacity(1);
+        q.push(1);
+        q.push_evicting(2, &mut EvictWith::new(|_: RangeIter<i32>, _: &i32| 2));
+    }
+}
@@ -27,9 +27,10 @@ where
     }
 }
 
-impl<T> Serialize for CircularQueue<T>
+impl<T, St> Serialize for CircularQueue<T, St>
 where
     T: Serialize,
+    St: Storage<T>,
 {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -70,6 +71,75 @@ where
     }
 }
 
+impl<T, X> Serialize for RecentSet<T, X>
+where
+    T: Serialize,
+{
+    /// Serializes the set in the same format as `CircularQueue`.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.queue.serialize(serializer)
+    }
+}
+
+impl<'de, T, X> Deserialize<'de> for RecentSet<T, X>
+where
+    T: Deserialize<'de> + Eq,
+    X: RecentSetIndex<T> + Default,
+{
+    /// Deserializes the set from the same format as `CircularQueue`, keeping only the newest of
+    /// any equal elements.
+    fn deserialize<D>(deserializer: D) -> Result<RecentSet<T, X>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let data = CircularQueueData::deserialize(deserializer)?;
+        let mut set = RecentSet::with_capacity(data.capacity);
+        for elem in data.values {
+            set.push(elem);
+        }
+        Ok(set)
+    }
+}
+
+impl<K, V, X> Serialize for LruRing<K, V, X>
+where
+    K: Serialize,
+    V: Serialize,
+{
+    /// Serializes the entries in the same format as a `CircularQueue` of pairs, going from the
+    /// least recently used one to the most recently used one.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.queue.serialize(serializer)
+    }
+}
+
+impl<'de, K, V, X> Deserialize<'de> for LruRing<K, V, X>
+where
+    K: Deserialize<'de> + Eq,
+    V: Deserialize<'de>,
+    X: RecentSetIndex<K> + Default,
+{
+    /// Deserializes the entries from the same format as a `CircularQueue` of pairs, keeping only
+    /// the newest of any entries with equal keys.
+    fn deserialize<D>(deserializer: D) -> Result<LruRing<K, V, X>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let data = CircularQueueData::deserialize(deserializer)?;
+        let mut ring = LruRing::with_capacity(data.capacity);
+        for (key, value) in data.values {
+            ring.insert(key, value);
+        }
+        Ok(ring)
+    }
+}
+
 #[cfg(feature = "serde_support_test")]
 #[cfg(test)]
 mod tests {
@@ -208,4 +278,45 @@ mod tests {
                 .unwrap();
         assert_eq!(oversize, q);
     }
+
+    #[test]
+    fn recent_set_round_trip() {
+        let mut set: RecentSet<i32> = RecentSet::with_capacity(3);
+        set.push(1);
+        set.push(2);
+        set.push(1);
+
+        let json = serde_json::to_string(&set).unwrap();
+        assert_eq!(json, r#"{"capacity":3,"values":[2,1]}"#);
+
+        let p = serde_json::from_str::<RecentSet<i32>>(&json).unwrap();
+        assert_eq!(p, &[2, 1][..]);
+        assert_eq!(p.capacity(), 3);
+    }
+
+    #[test]
+    fn recent_set_deserialize_duplicates() {
+        let p = serde_json::from_str::<RecentSet<i32, LinearIndex>>(
+            r#"{"capacity":3,"values":[1,2,1,3]}"#,
+        )
+        .unwrap();
+        assert_eq!(p, &[2, 1, 3][..]);
+    }
+
+    #[test]
+    fn lru_ring_round_trip() {
+        let mut ring: LruRing<i32, char> = LruRing::with_capacity(3);
+        ring.insert(1, 'a');
+        ring.insert(2, 'b');
+        ring.get(&1);
+
+        let json = serde_json::to_string(&ring).unwrap();
+        assert_eq!(json, r#"{"capacity":3,"values":[[2,"b"],[1,"a"]]}"#);
+
+        let mut p = serde_json::from_str::<LruRing<i32, char>>(&json).unwrap();
+        assert_eq!(p.capacity(), 3);
+        assert_eq!(p.as_queue(), &[(2, 'b'), (1, 'a')][..]);
+        assert_eq!(p.insert(3, 'c'), None);
+        assert_eq!(p.insert(4, 'd'), Some((2, 'b')));
+    }
 }
@@ -0,0 +1,738 @@
+//! A `CircularQueue` living in a shared memory mapping.
+//!
+//! One process creates the queue with `SharedQueueWriter::create()` and pushes into it, any number
+//! of other processes open the same file with `SharedQueueReader::open()` and take snapshots of
+//! its contents. Placing the file on a memory-backed file system (such as `/dev/shm` on Linux)
+//! avoids any disk I/O.
+//!
+//! The writer publishes every update with a sequence number that is odd while the update is in
+//! progress and even once it is complete. Readers copy the contents out and retry if the sequence
+//! number changed in the meantime, so a snapshot never contains a half-written update. A reader
+//! gives up with `io::ErrorKind::TimedOut` if the writer doesn't finish an update in time, which
+//! happens if the writer process dies in the middle of one.
+
+extern crate libc;
+
+use std::fs::{File, OpenOptions};
+use std::hint;
+use std::io;
+use std::marker::PhantomData;
+use std::mem::{self, ManuallyDrop, MaybeUninit};
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use super::*;
+
+const MAGIC: usize = 0x5143_6972; // "riCQ"
+
+/// How long `SharedQueueReader::snapshot()` waits for the writer to finish an update.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The header at the start of the mapping, followed by the slots.
+#[repr(C)]
+struct Header {
+    magic: AtomicUsize,
+    element_size: AtomicUsize,
+    capacity: AtomicUsize,
+    sequence: AtomicUsize,
+    len: AtomicUsize,
+    insertion_index: AtomicUsize,
+}
+
+/// A memory mapping of a shared queue file.
+struct Mapping<T> {
+    ptr: *mut u8,
+    size: usize,
+    capacity: usize,
+    _file: File,
+    _marker: PhantomData<T>,
+}
+
+#[inline]
+fn data_offset<T>() -> usize {
+    let align = mem::align_of::<T>();
+    mem::size_of::<Header>().div_ceil(align) * align
+}
+
+#[inline]
+fn mapping_size<T>(capacity: usize) -> Option<usize> {
+    mem::size_of::<T>()
+        .checked_mul(capacity)
+        .and_then(|x| x.checked_add(data_offset::<T>()))
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl<T: Copy> Mapping<T> {
+    fn new(file: File, size: usize, capacity: usize, writable: bool) -> io::Result<Self> {
+        let protection = if writable {
+            libc::PROT_READ | libc::PROT_WRITE
+        } else {
+            libc::PROT_READ
+        };
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                size,
+                protection,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self {
+            ptr: ptr as *mut u8,
+            size,
+            capacity,
+            _file: file,
+            _marker: PhantomData,
+        })
+    }
+
+    #[inline]
+    fn header(&self) -> &Header {
+        unsafe { &*(self.ptr as *const Header) }
+    }
+
+    #[inline]
+    fn slot(&self, index: usize) -> *mut T {
+        assert!(index < self.capacity, "slot index out of bounds");
+        unsafe { (self.ptr.add(data_offset::<T>()) as *mut T).add(index) }
+    }
+
+    /// Checks that the header still describes the queue the mapping was set up for.
+    ///
+    /// The header changes if `SharedQueueWriter::create()` reuses the file.
+    fn check_header(&self) -> io::Result<()> {
+        let header = self.header();
+        if header.magic.load(Ordering::Relaxed) != MAGIC
+            || header.element_size.load(Ordering::Relaxed) != mem::size_of::<T>()
+            || header.capacity.load(Ordering::Relaxed) != self.capacity
+        {
+            return Err(invalid_data(
+                "the queue was recreated with a different layout",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Creates a queue out of the copied slots.
+    ///
+    /// # Safety
+    ///
+    /// All of `data` must be initialized.
+    unsafe fn queue_from(
+        &self,
+        data: Vec<MaybeUninit<T>>,
+        insertion_index: usize,
+    ) -> CircularQueue<T> {
+        let mut data = ManuallyDrop::new(data);
+        let data = Vec::from_raw_parts(data.as_mut_ptr() as *mut T, data.len(), data.capacity());
+
+        let mut queue = CircularQueue::with_capacity(0);
+        queue.data = data;
+        queue.capacity = self.capacity;
+        queue.insertion_index = if self.capacity == 0 {
+            0
+        } else {
+            insertion_index % self.capacity
+        };
+        queue
+    }
+
+    /// Copies the contents out, retrying until the copy is consistent or `timeout` runs out.
+    fn snapshot(&self, timeout: Duration) -> io::Result<CircularQueue<T>> {
+        let header = self.header();
+        let start = Instant::now();
+        let mut data = Vec::with_capacity(self.capacity);
+
+        loop {
+            let sequence = header.sequence.load(Ordering::Acquire);
+
+            // An odd sequence number means that the writer is in the middle of an update.
+            if sequence & 1 == 0 {
+                self.check_header()?;
+
+                let len = header.len.load(Ordering::Relaxed);
+                let insertion_index = header.insertion_index.load(Ordering::Relaxed);
+
+                data.clear();
+                for i in 0..len.min(self.capacity) {
+                    // The writer may be updating the slot at the same time, so the copy stays
+                    // uninitialized until the sequence check below shows that it isn't torn.
+                    let slot = self.slot(i) as *const MaybeUninit<T>;
+                    data.push(unsafe { ptr::read_volatile(slot) });
+                }
+
+                fence(Ordering::Acquire);
+                if header.sequence.load(Ordering::Relaxed) == sequence {
+                    // The sequence number didn't change, so the writer didn't touch the slots while
+                    // they were being copied, and they hold values it wrote.
+                    return Ok(unsafe { self.queue_from(data, insertion_index) });
+                }
+            }
+
+            if start.elapsed() >= timeout {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "the writer didn't finish its update in time",
+                ));
+            }
+            hint::spin_loop();
+        }
+    }
+}
+
+impl<T> Drop for Mapping<T> {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, self.size);
+        }
+    }
+}
+
+/// The writing side of a `CircularQueue` shared between processes.
+///
+/// There can be only one writer per file at a time: it holds an exclusive `flock()` on the file
+/// for as long as it exists.
+pub struct SharedQueueWriter<T: Copy> {
+    mapping: Mapping<T>,
+    // The writer's own copies of the header fields, which it only publishes. Other processes can
+    // write to the file, so the values in the header can't be trusted for indexing.
+    len: usize,
+    insertion_index: usize,
+}
+
+/// The reading side of a `CircularQueue` shared between processes.
+pub struct SharedQueueReader<T: Copy> {
+    mapping: Mapping<T>,
+}
+
+unsafe impl<T: Copy + Send> Send for SharedQueueWriter<T> {}
+unsafe impl<T: Copy + Sync> Sync for SharedQueueWriter<T> {}
+unsafe impl<T: Copy + Send> Send for SharedQueueReader<T> {}
+unsafe impl<T: Copy + Sync> Sync for SharedQueueReader<T> {}
+
+impl<T: Copy> SharedQueueWriter<T> {
+    /// Creates a new, empty shared queue with the requested capacity at `path`.
+    ///
+    /// If the file already exists, its contents are discarded. The file is never shrunk, so
+    /// readers that still have it mapped aren't cut off, and their next snapshot fails with
+    /// `io::ErrorKind::InvalidData` if the new queue has a different capacity or element size.
+    /// Fails with `io::ErrorKind::WouldBlock` if another writer is using the file.
+    ///
+    /// `T` is stored in the file as is, so it should not contain pointers or references: they
+    /// would be meaningless in the other processes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::shm::SharedQueueWriter;
+    ///
+    /// # let path = std::env::temp_dir().join("circular-queue-doctest-create");
+    /// let mut writer = SharedQueueWriter::<u32>::create(&path, 3)?;
+    /// writer.push(1);
+    /// writer.push(2);
+    ///
+    /// assert_eq!(writer.len(), 2);
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn create<P: AsRef<Path>>(path: P, capacity: usize) -> io::Result<Self> {
+        let size = mapping_size::<T>(capacity)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "capacity is too large"))?;
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Resize only after taking the lock so that an existing writer's file stays intact.
+        // Shrinking the file would make the readers of a previous queue fault when they access
+        // the part that was cut off.
+        if file.metadata()?.len() < size as u64 {
+            file.set_len(size as u64)?;
+        }
+
+        let mapping = Mapping::new(file, size, capacity, true)?;
+
+        {
+            // Readers of a previous queue in this file see an update in progress until the new
+            // header is complete, and a different sequence number afterwards.
+            let header = mapping.header();
+            let sequence = header.sequence.load(Ordering::Relaxed) | 1;
+            header.sequence.store(sequence, Ordering::Relaxed);
+            fence(Ordering::Release);
+
+            header.magic.store(MAGIC, Ordering::Relaxed);
+            header
+                .element_size
+                .store(mem::size_of::<T>(), Ordering::Relaxed);
+            header.capacity.store(capacity, Ordering::Relaxed);
+            header.len.store(0, Ordering::Relaxed);
+            header.insertion_index.store(0, Ordering::Relaxed);
+
+            header
+                .sequence
+                .store(sequence.wrapping_add(1), Ordering::Release);
+        }
+
+        Ok(Self {
+            mapping,
+            len: 0,
+            insertion_index: 0,
+        })
+    }
+
+    /// Returns the current number of elements in the queue.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the queue contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the queue is full.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.capacity() == self.len()
+    }
+
+    /// Returns the capacity of the queue.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.mapping.capacity
+    }
+
+    /// Pushes a new element into the queue and publishes it to the readers.
+    ///
+    /// Once the capacity is reached, pushing new items will overwrite old ones.
+    ///
+    /// In case an old value is overwritten, it will be returned.
+    pub fn push(&mut self, x: T) -> Popped<T> {
+        let capacity = self.capacity();
+        if capacity == 0 {
+            return None;
+        }
+
+        let header = self.mapping.header();
+        let slot = self.mapping.slot(self.insertion_index);
+
+        let sequence = header.sequence.load(Ordering::Relaxed);
+        header
+            .sequence
+            .store(sequence.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        // Readers never write to the slots and there is only one writer, so nothing can be
+        // writing to the slot concurrently. Since the queue is full, the slot holds a value this
+        // writer pushed.
+        let old = if self.len == capacity {
+            Some(unsafe { ptr::read(slot) })
+        } else {
+            self.len += 1;
+            None
+        };
+        unsafe { ptr::write_volatile(slot, x) };
+        self.insertion_index = (self.insertion_index + 1) % capacity;
+
+        header.len.store(self.len, Ordering::Relaxed);
+        header
+            .insertion_index
+            .store(self.insertion_index, Ordering::Relaxed);
+
+        header
+            .sequence
+            .store(sequence.wrapping_add(2), Ordering::Release);
+
+        old
+    }
+
+    /// Clears the queue.
+    pub fn clear(&mut self) {
+        let header = self.mapping.header();
+        let sequence = header.sequence.load(Ordering::Relaxed);
+        header
+            .sequence
+            .store(sequence.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        self.len = 0;
+        self.insertion_index = 0;
+        header.len.store(0, Ordering::Relaxed);
+        header.insertion_index.store(0, Ordering::Relaxed);
+
+        header
+            .sequence
+            .store(sequence.wrapping_add(2), Ordering::Release);
+    }
+
+    /// Returns a copy of the queue's current contents.
+    pub fn snapshot(&self) -> CircularQueue<T> {
+        // As in `push()`, the slots hold values this writer pushed.
+        let data = (0..self.len)
+            .map(|i| unsafe { ptr::read(self.mapping.slot(i) as *const MaybeUninit<T>) })
+            .collect();
+        unsafe { self.mapping.queue_from(data, self.insertion_index) }
+    }
+}
+
+impl<T: Copy> SharedQueueReader<T> {
+    /// Opens a shared queue created by `SharedQueueWriter::create()`.
+    ///
+    /// # Safety
+    ///
+    /// The queue at `path` must have been created by `SharedQueueWriter<T>` with the same `T`
+    /// (down to its memory layout), otherwise the snapshots will contain invalid values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::shm::{SharedQueueReader, SharedQueueWriter};
+    ///
+    /// # let path = std::env::temp_dir().join("circular-queue-doctest-open");
+    /// let mut writer = SharedQueueWriter::<u32>::create(&path, 3)?;
+    /// writer.push(1);
+    /// writer.push(2);
+    /// writer.push(3);
+    /// writer.push(4);
+    ///
+    /// // Usually done in another process.
+    /// let reader = unsafe { SharedQueueReader::<u32>::open(&path)? };
+    /// let snapshot = reader.snapshot()?;
+    ///
+    /// let mut iter = snapshot.asc_iter();
+    /// assert_eq!(iter.next(), Some(&2));
+    /// assert_eq!(iter.next(), Some(&3));
+    /// assert_eq!(iter.next(), Some(&4));
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        // Readers never write, so they only need read access to the file.
+        let file = File::open(path)?;
+
+        let file_size = file.metadata()?.len();
+        if file_size < mem::size_of::<Header>() as u64 {
+            return Err(invalid_data("file is too small"));
+        }
+
+        let mut mapping = Mapping::new(file, file_size as usize, 0, false)?;
+        let (magic, element_size, capacity) = {
+            let header = mapping.header();
+            (
+                header.magic.load(Ordering::Acquire),
+                header.element_size.load(Ordering::Relaxed),
+                header.capacity.load(Ordering::Relaxed),
+            )
+        };
+
+        if magic != MAGIC {
+            return Err(invalid_data("not a shared queue file"));
+        }
+        if element_size != mem::size_of::<T>() {
+            return Err(invalid_data("element size mismatch"));
+        }
+        match mapping_size::<T>(capacity) {
+            Some(size) if size as u64 <= file_size => (),
+            _ => return Err(invalid_data("file is too small")),
+        }
+
+        mapping.capacity = capacity;
+        Ok(Self { mapping })
+    }
+
+    /// Returns the capacity of the queue.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.mapping.capacity
+    }
+
+    /// Returns the writer's sequence number.
+    ///
+    /// The sequence number changes every time the writer modifies the queue, so it can be polled to
+    /// find out whether a new snapshot is needed.
+    #[inline]
+    pub fn sequence(&self) -> usize {
+        self.mapping.header().sequence.load(Ordering::Acquire)
+    }
+
+    /// Returns a consistent copy of the queue's current contents.
+    ///
+    /// If the writer is in the middle of an update, waits up to a second for it to finish, see
+    /// `snapshot_timeout()`.
+    #[inline]
+    pub fn snapshot(&self) -> io::Result<CircularQueue<T>> {
+        self.snapshot_timeout(DEFAULT_TIMEOUT)
+    }
+
+    /// Returns a consistent copy of the queue's current contents, waiting up to `timeout` for the
+    /// writer to finish an update in progress.
+    ///
+    /// Fails with `io::ErrorKind::TimedOut` if no consistent copy could be made in time, for
+    /// example because the writer died in the middle of an update, and with
+    /// `io::ErrorKind::InvalidData` if the file was reused for a queue with a different capacity
+    /// or element size.
+    #[inline]
+    pub fn snapshot_timeout(&self, timeout: Duration) -> io::Result<CircularQueue<T>> {
+        self.mapping.snapshot(timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::format;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+    use std::string::String;
+    use std::thread;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("circular-queue-{}-{}", name, process::id()))
+    }
+
+    #[test]
+    fn snapshot_in_same_process() {
+        let path = temp_path("same-process");
+        let mut writer = SharedQueueWriter::<u64>::create(&path, 3).unwrap();
+        let reader = unsafe { SharedQueueReader::<u64>::open(&path).unwrap() };
+
+        assert!(reader.snapshot().unwrap().is_empty());
+        assert_eq!(reader.capacity(), 3);
+
+        for i in 1..6 {
+            writer.push(i);
+        }
+
+        let snapshot = reader.snapshot().unwrap();
+        assert_eq!(snapshot.capacity(), 3);
+        let res: Vec<_> = snapshot.asc_iter().cloned().collect();
+        assert_eq!(res, [3, 4, 5]);
+        assert_eq!(snapshot, writer.snapshot());
+
+        writer.clear();
+        assert!(reader.snapshot().unwrap().is_empty());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn push_returns_overwritten() {
+        let path = temp_path("push-returns");
+        let mut writer = SharedQueueWriter::<u8>::create(&path, 2).unwrap();
+
+        assert_eq!(writer.push(1), None);
+        assert_eq!(writer.push(2), None);
+        assert!(writer.is_full());
+        assert_eq!(writer.push(3), Some(1));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn single_writer() {
+        let path = temp_path("single-writer");
+        let _writer = SharedQueueWriter::<u8>::create(&path, 2).unwrap();
+
+        let err = SharedQueueWriter::<u8>::create(&path, 2).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn open_validates_header() {
+        let path = temp_path("validates-header");
+
+        fs::write(&path, [0u8; 256]).unwrap();
+        let err = unsafe { SharedQueueReader::<u8>::open(&path) }
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        {
+            let _writer = SharedQueueWriter::<u8>::create(&path, 2).unwrap();
+        }
+        let err = unsafe { SharedQueueReader::<u32>::open(&path) }
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn snapshots_are_consistent() {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        struct Pair(u64, u64);
+
+        let path = temp_path("consistent");
+        let mut writer = SharedQueueWriter::<Pair>::create(&path, 16).unwrap();
+        let reader = unsafe { SharedQueueReader::<Pair>::open(&path).unwrap() };
+
+        let handle = thread::spawn(move || {
+            for i in 0..100_000 {
+                writer.push(Pair(i, i));
+            }
+        });
+
+        while !handle.is_finished() {
+            let snapshot = reader.snapshot().unwrap();
+            let mut prev = None;
+            for &Pair(a, b) in snapshot.asc_iter() {
+                assert_eq!(a, b);
+                if let Some(prev) = prev {
+                    assert_eq!(a, prev + 1);
+                }
+                prev = Some(a);
+            }
+        }
+        handle.join().unwrap();
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn snapshot_times_out() {
+        let path = temp_path("times-out");
+        let mut writer = SharedQueueWriter::<u32>::create(&path, 2).unwrap();
+        writer.push(1);
+        let reader = unsafe { SharedQueueReader::<u32>::open(&path).unwrap() };
+
+        // Pretend that the writer died in the middle of an update.
+        let sequence = &writer.mapping.header().sequence;
+        sequence.fetch_add(1, Ordering::Relaxed);
+
+        let err = reader
+            .snapshot_timeout(Duration::from_millis(10))
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+        sequence.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(reader.snapshot().unwrap(), [1]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn recreate_with_smaller_capacity() {
+        let path = temp_path("recreate");
+        let reader = {
+            let mut writer = SharedQueueWriter::<u64>::create(&path, 1024).unwrap();
+            writer.push(1);
+            unsafe { SharedQueueReader::<u64>::open(&path).unwrap() }
+        };
+
+        let mut writer = SharedQueueWriter::<u64>::create(&path, 2).unwrap();
+        writer.push(2);
+        let err = reader.snapshot().err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // Recreating with the same layout keeps the readers working.
+        let reader = unsafe { SharedQueueReader::<u64>::open(&path).unwrap() };
+        drop(writer);
+        let mut writer = SharedQueueWriter::<u64>::create(&path, 2).unwrap();
+        assert!(reader.snapshot().unwrap().is_empty());
+        writer.push(3);
+        assert_eq!(reader.snapshot().unwrap(), [3]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn writer_ignores_corrupted_header() {
+        let path = temp_path("corrupted-header");
+        let mut writer = SharedQueueWriter::<u32>::create(&path, 2).unwrap();
+        writer.push(1);
+        let reader = unsafe { SharedQueueReader::<u32>::open(&path).unwrap() };
+
+        // Another process with write access to the file could do this.
+        let header = writer.mapping.header();
+        header.len.store(usize::MAX, Ordering::Relaxed);
+        header.insertion_index.store(usize::MAX, Ordering::Relaxed);
+
+        assert_eq!(writer.push(2), None);
+        assert_eq!(writer.push(3), Some(1));
+        assert_eq!(writer.snapshot(), [2, 3]);
+        assert_eq!(reader.snapshot().unwrap(), [2, 3]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn open_read_only_file() {
+        let path = temp_path("read-only");
+        let mut writer = SharedQueueWriter::<u32>::create(&path, 2).unwrap();
+        writer.push(1);
+
+        let mut permissions = fs::metadata(&path).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&path, permissions).unwrap();
+
+        let reader = unsafe { SharedQueueReader::<u32>::open(&path).unwrap() };
+        writer.push(2);
+        assert_eq!(reader.snapshot().unwrap(), [1, 2]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    const CHILD_PATH_VAR: &str = "CIRCULAR_QUEUE_SHM_TEST_PATH";
+
+    #[test]
+    fn snapshot_in_another_process() {
+        let path = temp_path("another-process");
+        let mut writer = SharedQueueWriter::<u32>::create(&path, 4).unwrap();
+        for i in 0..6 {
+            writer.push(i);
+        }
+
+        // Run `another_process_child()` alone in a fresh copy of the test binary.
+        let output = process::Command::new(env::current_exe().unwrap())
+            .args(["--exact", "shm::tests::another_process_child"])
+            .env(CHILD_PATH_VAR, &path)
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success(), "{}", stdout);
+        assert!(stdout.contains("1 passed"), "{}", stdout);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn another_process_child() {
+        // Does nothing unless run by `snapshot_in_another_process()`.
+        let path = match env::var_os(CHILD_PATH_VAR) {
+            Some(path) => path,
+            None => return,
+        };
+
+        let reader = unsafe { SharedQueueReader::<u32>::open(path).unwrap() };
+        assert_eq!(reader.snapshot().unwrap(), [2, 3, 4, 5]);
+    }
+}
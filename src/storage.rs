@@ -0,0 +1,371 @@
+//! Storage backends for `CircularQueue`.
+
+#[cfg(has_alloc)]
+use super::*;
+
+#[cfg(feature = "allocator_api")]
+extern crate allocator_api2;
+
+#[cfg(feature = "allocator_api")]
+use self::allocator_api2::alloc::Allocator;
+
+/// A backing store for the elements of a `CircularQueue`.
+///
+/// A storage holds a contiguous sequence of initialized elements, like a `Vec`. The queue only
+/// pushes elements to the end while the storage has room, overwrites them in place and truncates
+/// the storage.
+pub trait Storage<T> {
+    /// Returns the initialized elements.
+    fn as_slice(&self) -> &[T];
+
+    /// Returns the initialized elements.
+    fn as_mut_slice(&mut self) -> &mut [T];
+
+    /// Returns the number of elements the storage can hold without growing.
+    fn capacity(&self) -> usize;
+
+    /// Grows the storage to hold at least `additional` more elements.
+    fn reserve_exact(&mut self, additional: usize);
+
+    /// Appends an element to the end.
+    ///
+    /// The queue calls this only when the storage can hold more elements.
+    fn push(&mut self, value: T);
+
+    /// Removes the last element and returns it, or `None` if the storage is empty.
+    fn pop(&mut self) -> Option<T>;
+
+    /// Drops the elements starting at `len`.
+    fn truncate(&mut self, len: usize);
+
+    /// Appends copies of `values` to the end.
+    ///
+    /// The queue calls this only when the storage can hold all of them. The default
+    /// implementation pushes them one by one.
+    #[inline]
+    fn extend_from_slice(&mut self, values: &[T])
+    where
+        T: Copy,
+    {
+        for &x in values {
+            self.push(x);
+        }
+    }
+}
+
+#[cfg(has_alloc)]
+impl<T> Storage<T> for Vec<T> {
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        Vec::capacity(self)
+    }
+
+    #[inline]
+    fn reserve_exact(&mut self, additional: usize) {
+        Vec::reserve_exact(self, additional)
+    }
+
+    #[inline]
+    fn push(&mut self, value: T) {
+        Vec::push(self, value)
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        Vec::pop(self)
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        Vec::truncate(self, len)
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, values: &[T])
+    where
+        T: Copy,
+    {
+        Vec::extend_from_slice(self, values)
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T, A: Allocator> Storage<T> for allocator_api2::vec::Vec<T, A> {
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        allocator_api2::vec::Vec::capacity(self)
+    }
+
+    #[inline]
+    fn reserve_exact(&mut self, additional: usize) {
+        allocator_api2::vec::Vec::reserve_exact(self, additional)
+    }
+
+    #[inline]
+    fn push(&mut self, value: T) {
+        allocator_api2::vec::Vec::push(self, value)
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        allocator_api2::vec::Vec::pop(self)
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        allocator_api2::vec::Vec::truncate(self, len)
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, values: &[T])
+    where
+        T: Copy,
+    {
+        allocator_api2::vec::Vec::extend_from_slice(self, values)
+    }
+}
+
+/// A `CircularQueue<T>` allocating its elements with the allocator `A`.
+///
+/// Besides the methods available for every storage, it supports `set_capacity()`, `into_vec()`,
+/// `into_vec_asc()` and conversions from and into `allocator_api2::vec::Vec<T, A>`. The remaining
+/// `Vec`-specific parts of `CircularQueue<T>` aren't available: `builder()`,
+/// `try_set_capacity()`, `into_boxed_slice()`, `split_off()`, `merge_by_key()`, the `VecDeque`
+/// conversions and the `Default` and `FromIterator` implementations.
+#[cfg(feature = "allocator_api")]
+pub type CircularQueueIn<T, A> = CircularQueue<T, allocator_api2::vec::Vec<T, A>>;
+
+#[cfg(feature = "allocator_api")]
+impl<T, A: Allocator> CircularQueue<T, allocator_api2::vec::Vec<T, A>> {
+    /// Constructs a new, empty queue with zero capacity in the provided allocator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate allocator_api2;
+    /// extern crate circular_queue;
+    ///
+    /// use allocator_api2::alloc::Global;
+    /// use circular_queue::CircularQueueIn;
+    ///
+    /// # fn main() {
+    /// let queue: CircularQueueIn<i32, _> = CircularQueueIn::new_in(Global);
+    /// assert_eq!(queue.capacity(), 0);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_capacity_in(0, alloc)
+    }
+
+    /// Constructs a new, empty queue with the requested capacity in the provided allocator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate allocator_api2;
+    /// extern crate circular_queue;
+    ///
+    /// use allocator_api2::alloc::Global;
+    /// use circular_queue::CircularQueueIn;
+    ///
+    /// # fn main() {
+    /// let mut queue = CircularQueueIn::with_capacity_in(3, Global);
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.push(3);
+    /// queue.push(4);
+    ///
+    /// assert_eq!(queue, vec![2, 3, 4]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        CircularQueue {
+            data: allocator_api2::vec::Vec::with_capacity_in(capacity, alloc),
+            capacity,
+            insertion_index: 0,
+            overflow_policy: OverflowPolicy::Overwrite,
+            evictions: Evictions::new(),
+        }
+    }
+
+    /// Returns a reference to the underlying allocator.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.data.allocator()
+    }
+
+    /// Changes the capacity of the queue.
+    ///
+    /// If the new capacity is smaller than the current length, the oldest elements are dropped.
+    /// If it is larger, memory for the whole new capacity is reserved in the queue's allocator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate allocator_api2;
+    /// extern crate circular_queue;
+    ///
+    /// use allocator_api2::alloc::Global;
+    /// use circular_queue::CircularQueueIn;
+    ///
+    /// # fn main() {
+    /// let mut queue = CircularQueueIn::with_capacity_in(3, Global);
+    /// queue.extend(vec![1, 2, 3, 4]);
+    ///
+    /// queue.set_capacity(2);
+    /// assert_eq!(queue, vec![3, 4]);
+    ///
+    /// queue.set_capacity(4);
+    /// queue.push(5);
+    /// assert_eq!(queue, vec![3, 4, 5]);
+    /// # }
+    /// ```
+    pub fn set_capacity(&mut self, capacity: usize) {
+        if capacity > self.len() {
+            let additional = capacity - self.len();
+            self.data.reserve_exact(additional);
+        }
+
+        self.set_capacity_reserved(capacity);
+    }
+
+    /// Converts the queue into a `Vec<T, A>` going from the most recently pushed items to the
+    /// oldest ones.
+    #[inline]
+    pub fn into_vec(mut self) -> allocator_api2::vec::Vec<T, A> {
+        self.data[self.insertion_index..].reverse(); // Reverse the upper part.
+        self.data[..self.insertion_index].reverse(); // Reverse the lower part.
+        self.data
+    }
+
+    /// Converts the queue into a `Vec<T, A>` going from the oldest items to the most recently
+    /// pushed ones.
+    #[inline]
+    pub fn into_vec_asc(mut self) -> allocator_api2::vec::Vec<T, A> {
+        self.normalize();
+        self.data
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T, A: Allocator> From<CircularQueueIn<T, A>> for allocator_api2::vec::Vec<T, A> {
+    #[inline]
+    fn from(queue: CircularQueueIn<T, A>) -> Self {
+        queue.into_vec()
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T, A: Allocator> From<allocator_api2::vec::Vec<T, A>> for CircularQueueIn<T, A> {
+    /// Converts a `Vec<T, A>` going from the oldest items to the newest ones into a full queue
+    /// with capacity equal to the length of the `Vec`, reusing the allocation.
+    #[inline]
+    fn from(data: allocator_api2::vec::Vec<T, A>) -> Self {
+        CircularQueue {
+            capacity: data.len(),
+            data,
+            insertion_index: 0,
+            overflow_policy: OverflowPolicy::Overwrite,
+            evictions: Evictions::new(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "allocator_api"))]
+mod tests {
+    use super::*;
+
+    use self::allocator_api2::alloc::{AllocError, Global};
+    use alloc::vec;
+    use core::alloc::Layout;
+    use core::cell::Cell;
+    use core::ptr::NonNull;
+
+    /// An allocator counting the allocations made through it.
+    struct Counting<'a>(&'a Cell<usize>);
+
+    unsafe impl<'a> Allocator for Counting<'a> {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.0.set(self.0.get() + 1);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    #[test]
+    fn with_capacity_in() {
+        let count = Cell::new(0);
+        let mut q = CircularQueue::with_capacity_in(3, Counting(&count));
+        assert_eq!(count.get(), 1);
+
+        for i in 0..10 {
+            q.push(i);
+        }
+        assert_eq!(count.get(), 1);
+        assert_eq!(q, vec![7, 8, 9]);
+
+        let mut other = CircularQueue::with_capacity(3);
+        other.push(7);
+        other.push(8);
+        other.push(9);
+        assert_eq!(q, other);
+    }
+
+    #[test]
+    fn new_in() {
+        let count = Cell::new(0);
+        let mut q = CircularQueue::new_in(Counting(&count));
+        q.push(1);
+        assert!(q.is_empty());
+        assert_eq!(count.get(), 0);
+    }
+
+    #[test]
+    fn set_capacity_and_convert() {
+        let count = Cell::new(0);
+        let mut q = CircularQueue::with_capacity_in(3, Counting(&count));
+        q.extend(vec![1, 2, 3, 4]);
+
+        q.set_capacity(2);
+        assert_eq!(q, vec![3, 4]);
+        q.set_capacity(5);
+        q.extend(vec![5, 6, 7, 8]);
+        assert_eq!(q, vec![4, 5, 6, 7, 8]);
+        assert_eq!(count.get(), 2);
+
+        let v = q.into_vec_asc();
+        assert_eq!(v, [4, 5, 6, 7, 8]);
+        let mut q = CircularQueueIn::from(v);
+        assert!(q.is_full());
+        assert_eq!(q.push(9), Some(4));
+
+        let v: allocator_api2::vec::Vec<_, _> = q.into();
+        assert_eq!(v, [9, 8, 7, 6, 5]);
+        assert_eq!(count.get(), 2);
+    }
+}
@@ -0,0 +1,332 @@
+//! Text formatting into queues of bytes and characters.
+
+use super::*;
+
+#[cfg(has_extern_crate_alloc)]
+use core::fmt::Write;
+#[cfg(has_extern_crate_alloc)]
+use core::str;
+
+#[cfg(not(has_extern_crate_alloc))]
+use std::fmt::Write;
+#[cfg(not(has_extern_crate_alloc))]
+use std::str;
+
+impl<S: Storage<u8>> Write for CircularQueue<u8, S> {
+    /// Pushes the UTF-8 bytes of `s` into the queue.
+    ///
+    /// Fails if the overflow policy is `OverflowPolicy::Reject` and not all of the bytes fit.
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.write_from_slice(s.as_bytes()) == s.len() {
+            Ok(())
+        } else {
+            Err(fmt::Error)
+        }
+    }
+}
+
+impl<S: Storage<char>> Write for CircularQueue<char, S> {
+    /// Pushes the characters of `s` into the queue.
+    ///
+    /// Fails if the overflow policy is `OverflowPolicy::Reject` and not all of the characters fit.
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            self.write_char(c)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        if self.is_full() && self.overflow_policy == OverflowPolicy::Reject {
+            return Err(fmt::Error);
+        }
+        self.push(c);
+        Ok(())
+    }
+}
+
+impl<S: Storage<u8>> Write for ByteQueue<S> {
+    /// Writes the UTF-8 bytes of `s`.
+    ///
+    /// Fails if the overflow policy is `OverflowPolicy::Reject` and not all of the bytes fit.
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.write(s.as_bytes()) == s.len() {
+            Ok(())
+        } else {
+            Err(fmt::Error)
+        }
+    }
+}
+
+impl<S: Storage<u8>> CircularQueue<u8, S> {
+    /// Returns the contents of the queue decoded as UTF-8, going from the oldest bytes to the
+    /// newest ones.
+    ///
+    /// The returned value implements `Display`. If the queue has evicted bytes, up to three
+    /// continuation bytes at the oldest end are skipped as the rest of a character whose beginning
+    /// was overwritten. Any other invalid UTF-8, including continuation bytes at the oldest end of
+    /// a queue that hasn't lost any bytes and a character cut short at the newest end, is shown as
+    /// `U+FFFD REPLACEMENT CHARACTER`, one per invalid sequence like in
+    /// `String::from_utf8_lossy()`. Characters spanning the wrap point of the queue are decoded as
+    /// usual.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    /// use std::fmt::Write;
+    ///
+    /// let mut log = CircularQueue::with_capacity(14);
+    /// write!(log, "température: {}°C", 21).unwrap();
+    ///
+    /// // The first byte of 'é' was overwritten, so the rest of it is skipped.
+    /// assert_eq!(log.dump_utf8().to_string(), "rature: 21°C");
+    /// ```
+    #[inline]
+    pub fn dump_utf8<'a>(&'a self) -> Utf8Dump<'a> {
+        let (newer, older) = self.data.as_slice().split_at(self.insertion_index);
+        let truncated = self.total_evicted() > 0 || (self.is_full() && self.insertion_index != 0);
+        Utf8Dump {
+            older,
+            newer,
+            truncated,
+        }
+    }
+}
+
+/// The contents of a `CircularQueue<u8>` decoded as UTF-8, created with
+/// `CircularQueue::dump_utf8()`.
+#[derive(Clone, Copy, Debug)]
+pub struct Utf8Dump<'a> {
+    older: &'a [u8],
+    newer: &'a [u8],
+    // Whether the oldest bytes may be the rest of a character that was evicted.
+    truncated: bool,
+}
+
+impl<'a> Utf8Dump<'a> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.older.len() + self.newer.len()
+    }
+
+    #[inline]
+    fn byte(&self, index: usize) -> u8 {
+        if index < self.older.len() {
+            self.older[index]
+        } else {
+            self.newer[index - self.older.len()]
+        }
+    }
+
+    /// Returns the bytes from `index` up to the end of the half containing it.
+    #[inline]
+    fn run(&self, index: usize) -> &'a [u8] {
+        if index < self.older.len() {
+            &self.older[index..]
+        } else {
+            &self.newer[index - self.older.len()..]
+        }
+    }
+
+    /// Returns the length of the invalid UTF-8 sequence at `index`.
+    ///
+    /// Like in `String::from_utf8_lossy()`, the longest prefix of a valid sequence counts as one
+    /// invalid sequence, and any other byte counts as one on its own.
+    fn invalid_len(&self, index: usize) -> usize {
+        let first = self.byte(index);
+        let width = utf8_width(first);
+        if width < 2 || first < 0xC2 || first > 0xF4 {
+            return 1;
+        }
+
+        // The second byte is restricted further to rule out overlong encodings, surrogates and
+        // code points above U+10FFFF.
+        let (low, high) = match first {
+            0xE0 => (0xA0, 0xBF),
+            0xED => (0x80, 0x9F),
+            0xF0 => (0x90, 0xBF),
+            0xF4 => (0x80, 0x8F),
+            _ => (0x80, 0xBF),
+        };
+
+        let mut len = 1;
+        while len < width && index + len < self.len() {
+            let byte = self.byte(index + len);
+            let (low, high) = if len == 1 { (low, high) } else { (0x80, 0xBF) };
+            if byte < low || byte > high {
+                break;
+            }
+            len += 1;
+        }
+        len
+    }
+}
+
+/// Returns the length of a UTF-8 sequence starting with `byte`, or 0 for a continuation byte
+/// or an invalid one.
+#[inline]
+fn utf8_width(byte: u8) -> usize {
+    match (!byte).leading_zeros() {
+        0 => 1,
+        2 => 2,
+        3 => 3,
+        4 => 4,
+        _ => 0,
+    }
+}
+
+impl<'a> fmt::Display for Utf8Dump<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let len = self.len();
+        let mut i = 0;
+
+        while self.truncated && i < cmp::min(len, 3) && self.byte(i) & 0xC0 == 0x80 {
+            i += 1;
+        }
+
+        while i < len {
+            let run = self.run(i);
+            let valid = match str::from_utf8(run) {
+                Ok(s) => s,
+                Err(e) => unsafe { str::from_utf8_unchecked(&run[..e.valid_up_to()]) },
+            };
+            f.write_str(valid)?;
+            i += valid.len();
+
+            if valid.len() == run.len() {
+                continue;
+            }
+
+            // Either an invalid sequence or a character spanning the two halves.
+            let width = utf8_width(self.byte(i));
+            if width > 0 && i + width <= len {
+                let mut buf = [0; 4];
+                for (k, b) in buf[..width].iter_mut().enumerate() {
+                    *b = self.byte(i + k);
+                }
+                if let Ok(s) = str::from_utf8(&buf[..width]) {
+                    f.write_str(s)?;
+                    i += width;
+                    continue;
+                }
+            }
+
+            f.write_char('\u{FFFD}')?;
+            i += self.invalid_len(i);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, has_alloc))]
+mod tests {
+    use super::*;
+    #[cfg(has_extern_crate_alloc)]
+    use alloc::string::{String, ToString};
+    #[cfg(has_extern_crate_alloc)]
+    use alloc::vec;
+
+    #[test]
+    fn write_bytes() {
+        let mut q = CircularQueue::<u8>::with_capacity(8);
+        write!(q, "{}-{}", 12, 34).unwrap();
+        assert_eq!(q, b"12-34".to_vec());
+
+        q.write_str("abcdef").unwrap();
+        assert_eq!(q, b"34abcdef".to_vec());
+
+        q.set_overflow_policy(OverflowPolicy::Reject);
+        assert!(write!(q, "x").is_err());
+    }
+
+    #[test]
+    fn write_chars() {
+        let mut q = CircularQueue::<char>::with_capacity(4);
+        let euro = '€';
+        write!(q, "añb{}", euro).unwrap();
+        assert_eq!(q, vec!['a', 'ñ', 'b', '€']);
+
+        q.write_char('c').unwrap();
+        assert_eq!(q, vec!['ñ', 'b', '€', 'c']);
+
+        q.set_overflow_policy(OverflowPolicy::Reject);
+        assert!(q.write_char('d').is_err());
+        assert!(q.write_str("").is_ok());
+    }
+
+    #[test]
+    fn write_byte_queue() {
+        let mut q = ByteQueue::with_capacity(4);
+        q.set_overflow_policy(OverflowPolicy::Reject);
+        write!(q, "ab").unwrap();
+        assert!(write!(q, "cde").is_err());
+        assert!(q.is_full());
+    }
+
+    fn dump(q: &CircularQueue<u8>) -> String {
+        q.dump_utf8().to_string()
+    }
+
+    #[test]
+    fn dump_utf8_every_cut() {
+        let text = "a€b😀cñ";
+
+        // Every way of cutting the text and wrapping it around the queue.
+        for capacity in 1..text.len() + 1 {
+            for offset in 0..capacity {
+                let mut q = CircularQueue::with_capacity(capacity);
+                for _ in 0..offset {
+                    q.push(b'x');
+                }
+                q.write_str(text).unwrap();
+
+                let kept = &text.as_bytes()[text.len() - cmp::min(capacity, text.len())..];
+                let expected = String::from_utf8_lossy(kept);
+                let expected = expected.trim_start_matches('\u{FFFD}');
+                assert_eq!(dump(&q), expected, "{} {}", capacity, offset);
+            }
+        }
+    }
+
+    #[test]
+    fn dump_utf8_invalid() {
+        let mut q = CircularQueue::with_capacity(16);
+        q.extend(&[b'a', 0xFF, b'b', 0xE2, 0x82, b'c', 0xE2, 0x82]);
+        assert_eq!(dump(&q), "a\u{FFFD}b\u{FFFD}c\u{FFFD}");
+
+        // Continuation bytes at the oldest end are invalid if nothing was evicted before them.
+        let mut q = CircularQueue::with_capacity(16);
+        q.extend(&[0x80, 0x80, b'a']);
+        assert_eq!(dump(&q), "\u{FFFD}\u{FFFD}a");
+
+        // Otherwise they're skipped, but only up to the length of a character.
+        let mut q = CircularQueue::with_capacity(5);
+        q.extend(&[b'x', 0x80, 0x80, 0x80, 0x80, b'a']);
+        assert_eq!(dump(&q), "\u{FFFD}a");
+    }
+
+    #[test]
+    fn dump_utf8_matches_lossy() {
+        let bytes = [
+            b'a', 0xE0, 0x80, 0xF0, 0x9F, 0x98, b'b', 0xED, 0xA0, 0x80, 0xC0, 0xAF, 0xF4, 0x90,
+            0xF5, 0xE2, 0x82, 0xAC, 0xF0, 0x9F, 0x98, 0x80, 0xE2, 0x82,
+        ];
+        let expected = String::from_utf8_lossy(&bytes);
+
+        // Every wrap point.
+        for offset in 0..bytes.len() {
+            let mut q = CircularQueue::with_capacity(bytes.len());
+            for _ in 0..offset {
+                q.push(b'x');
+            }
+            q.extend(&bytes);
+            assert_eq!(dump(&q), expected, "{}", offset);
+        }
+    }
+}
@@ -0,0 +1,536 @@
+//! Windowed iteration over the queue's contents across the wrap point.
+
+use super::*;
+
+#[cfg(has_extern_crate_alloc)]
+use core::ops::Index;
+#[cfg(not(has_extern_crate_alloc))]
+use std::ops::Index;
+
+/// Returns the elements at logical indices `start..end` of the sequence `older` followed by
+/// `newer` as two slices.
+///
+/// The first slice is empty only if the range is empty.
+#[inline]
+pub(crate) fn logical_slices<'a, T>(
+    older: &'a [T],
+    newer: &'a [T],
+    start: usize,
+    end: usize,
+) -> (&'a [T], &'a [T]) {
+    let split = older.len();
+    if end <= split {
+        (&older[start..end], &[])
+    } else if start >= split {
+        (&newer[start - split..end - split], &[])
+    } else {
+        (&older[start..], &newer[..end - split])
+    }
+}
+
+/// A contiguous run of the queue's contents that may span the wrap point, going from the oldest
+/// items to the newest ones.
+///
+/// Yielded by `Windows` and `Chunks`.
+pub struct Window<'a, T: 'a> {
+    older: &'a [T],
+    newer: &'a [T],
+}
+
+impl<'a, T> Window<'a, T> {
+    #[inline]
+    fn new(older: &'a [T], newer: &'a [T], start: usize, end: usize) -> Self {
+        let (older, newer) = logical_slices(older, newer, start, end);
+        Window { older, newer }
+    }
+
+    /// Returns the number of elements in the window.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.older.len() + self.newer.len()
+    }
+
+    /// Returns `true` if the window contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the elements of the window as two slices, going from the oldest items to the
+    /// newest ones.
+    ///
+    /// The second slice is non-empty only if the window spans the wrap point of the queue.
+    #[inline]
+    pub fn as_slices(&self) -> (&'a [T], &'a [T]) {
+        (self.older, self.newer)
+    }
+
+    /// Returns the element at `index`, counting from the oldest one, or `None` if it's out of
+    /// bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        if index < self.older.len() {
+            Some(&self.older[index])
+        } else {
+            self.newer.get(index - self.older.len())
+        }
+    }
+
+    /// Returns an iterator over the window, going from the oldest items to the newest ones.
+    #[inline]
+    pub fn iter(&self) -> AscIter<'a, T> {
+        self.older.iter().chain(self.newer.iter())
+    }
+}
+
+impl<'a, T> Clone for Window<'a, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for Window<'a, T> {}
+
+impl<'a, T> Index<usize> for Window<'a, T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(x) => x,
+            None => panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                self.len(),
+                index
+            ),
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for Window<'a, T> {
+    type Item = &'a T;
+    type IntoIter = AscIter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, U> PartialEq<[U]> for Window<'a, T>
+where
+    T: PartialEq<U>,
+{
+    #[inline]
+    fn eq(&self, other: &[U]) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+
+        let (other_older, other_newer) = other.split_at(self.older.len());
+        self.older == other_older && self.newer == other_newer
+    }
+}
+
+impl<'a, 'b, T, U> PartialEq<&'b [U]> for Window<'a, T>
+where
+    T: PartialEq<U>,
+{
+    #[inline]
+    fn eq(&self, other: &&'b [U]) -> bool {
+        *self == **other
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for Window<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// An iterator over overlapping windows of the queue's contents, created with
+/// `CircularQueue::windows()`.
+pub struct Windows<'a, T: 'a> {
+    older: &'a [T],
+    newer: &'a [T],
+    size: usize,
+    // The logical index of the first element of the next window from the front.
+    front: usize,
+    // One past the logical index of the first element of the next window from the back.
+    back: usize,
+}
+
+impl<'a, T> Iterator for Windows<'a, T> {
+    type Item = Window<'a, T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        let start = self.front;
+        self.front += 1;
+        Some(Window::new(
+            self.older,
+            self.newer,
+            start,
+            start + self.size,
+        ))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Windows<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        let start = self.back;
+        Some(Window::new(
+            self.older,
+            self.newer,
+            start,
+            start + self.size,
+        ))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Windows<'a, T> {}
+
+impl<'a, T> Clone for Windows<'a, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Windows { ..*self }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for Windows<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over non-overlapping chunks of the queue's contents, created with
+/// `CircularQueue::chunks()`.
+pub struct Chunks<'a, T: 'a> {
+    older: &'a [T],
+    newer: &'a [T],
+    size: usize,
+    // The index of the next chunk from the front.
+    front: usize,
+    // One past the index of the next chunk from the back.
+    back: usize,
+}
+
+impl<'a, T> Chunks<'a, T> {
+    #[inline]
+    fn chunk(&self, index: usize) -> Window<'a, T> {
+        let len = self.older.len() + self.newer.len();
+        let start = index * self.size;
+        let end = cmp::min(start + self.size, len);
+        Window::new(self.older, self.newer, start, end)
+    }
+}
+
+impl<'a, T> Iterator for Chunks<'a, T> {
+    type Item = Window<'a, T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.front += 1;
+        Some(self.chunk(self.front - 1))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Chunks<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        Some(self.chunk(self.back))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Chunks<'a, T> {}
+
+impl<'a, T> Clone for Chunks<'a, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Chunks { ..*self }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for Chunks<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over pairs of consecutive elements of the queue's contents, created with
+/// `CircularQueue::pairs()`.
+pub struct Pairs<'a, T: 'a> {
+    windows: Windows<'a, T>,
+}
+
+impl<'a, T> Iterator for Pairs<'a, T> {
+    type Item = (&'a T, &'a T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.windows
+            .next()
+            .map(|w| (w.get(0).unwrap(), w.get(1).unwrap()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.windows.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Pairs<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.windows
+            .next_back()
+            .map(|w| (w.get(0).unwrap(), w.get(1).unwrap()))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Pairs<'a, T> {}
+
+impl<'a, T> Clone for Pairs<'a, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Pairs {
+            windows: self.windows.clone(),
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for Pairs<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<T, S: Storage<T>> CircularQueue<T, S> {
+    /// Returns an iterator over all overlapping windows of `size` consecutive elements, going
+    /// from the oldest items to the newest ones.
+    ///
+    /// Windows that span the wrap point of the queue are yielded like any other. If the queue
+    /// holds fewer than `size` elements, the iterator yields nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut samples = CircularQueue::with_capacity(4);
+    /// for x in 1..7 {
+    ///     samples.push(x);
+    /// }
+    ///
+    /// let sums: Vec<i32> = samples.windows(3).map(|w| w.iter().sum()).collect();
+    /// assert_eq!(sums, [12, 15]);
+    /// ```
+    #[inline]
+    pub fn windows<'a>(&'a self, size: usize) -> Windows<'a, T> {
+        assert!(size != 0, "window size must be non-zero");
+
+        let (newer, older) = self.data.as_slice().split_at(self.insertion_index);
+        Windows {
+            older,
+            newer,
+            size,
+            front: 0,
+            back: (self.len() + 1).saturating_sub(size),
+        }
+    }
+
+    /// Returns an iterator over non-overlapping chunks of `size` consecutive elements, going from
+    /// the oldest items to the newest ones.
+    ///
+    /// The last chunk holds the newest items and is shorter if the length of the queue isn't
+    /// divisible by `size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::with_capacity(5);
+    /// for x in 1..8 {
+    ///     queue.push(x);
+    /// }
+    ///
+    /// let mut chunks = queue.chunks(2);
+    /// assert_eq!(chunks.next().unwrap(), &[3, 4][..]);
+    /// assert_eq!(chunks.next().unwrap(), &[5, 6][..]);
+    /// assert_eq!(chunks.next().unwrap(), &[7][..]);
+    /// assert!(chunks.next().is_none());
+    /// ```
+    #[inline]
+    pub fn chunks<'a>(&'a self, size: usize) -> Chunks<'a, T> {
+        assert!(size != 0, "chunk size must be non-zero");
+
+        let (newer, older) = self.data.as_slice().split_at(self.insertion_index);
+        // The last chunk is the remainder, if any.
+        let count = self.len() / size + cmp::min(self.len() % size, 1);
+
+        Chunks {
+            older,
+            newer,
+            size,
+            front: 0,
+            back: count,
+        }
+    }
+
+    /// Returns an iterator over pairs of consecutive elements, going from the oldest items to the
+    /// newest ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circular_queue::CircularQueue;
+    ///
+    /// let mut timestamps = CircularQueue::with_capacity(3);
+    /// for &t in &[10, 15, 17, 24] {
+    ///     timestamps.push(t);
+    /// }
+    ///
+    /// let deltas: Vec<i32> = timestamps.pairs().map(|(a, b)| b - a).collect();
+    /// assert_eq!(deltas, [2, 7]);
+    /// ```
+    #[inline]
+    pub fn pairs<'a>(&'a self) -> Pairs<'a, T> {
+        Pairs {
+            windows: self.windows(2),
+        }
+    }
+}
+
+#[cfg(all(test, has_alloc))]
+mod tests {
+    use super::*;
+    #[cfg(has_extern_crate_alloc)]
+    use alloc::{format, vec};
+    use tests::wrapped_queues;
+
+    fn to_vec(w: Window<usize>) -> Vec<usize> {
+        w.iter().cloned().collect()
+    }
+
+    #[test]
+    fn windows() {
+        for q in wrapped_queues(6) {
+            let v: Vec<usize> = q.asc_iter().cloned().collect();
+
+            for size in 1..8 {
+                let expected: Vec<Vec<usize>> = v.windows(size).map(|w| w.to_vec()).collect();
+
+                let windows = q.windows(size);
+                assert_eq!(windows.len(), expected.len());
+                assert_eq!(windows.map(to_vec).collect::<Vec<_>>(), expected);
+
+                let mut rev: Vec<_> = q.windows(size).rev().map(to_vec).collect();
+                rev.reverse();
+                assert_eq!(rev, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn chunks() {
+        for q in wrapped_queues(6) {
+            let v: Vec<usize> = q.asc_iter().cloned().collect();
+
+            for size in 1..8 {
+                let expected: Vec<Vec<usize>> = v.chunks(size).map(|c| c.to_vec()).collect();
+
+                let chunks = q.chunks(size);
+                assert_eq!(chunks.len(), expected.len());
+                assert_eq!(chunks.map(to_vec).collect::<Vec<_>>(), expected);
+
+                let mut rev: Vec<_> = q.chunks(size).rev().map(to_vec).collect();
+                rev.reverse();
+                assert_eq!(rev, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn pairs() {
+        for q in wrapped_queues(5) {
+            let v: Vec<usize> = q.asc_iter().cloned().collect();
+            let expected: Vec<(usize, usize)> = v.windows(2).map(|w| (w[0], w[1])).collect();
+
+            let pairs = q.pairs();
+            assert_eq!(pairs.len(), expected.len());
+            assert_eq!(pairs.map(|(&a, &b)| (a, b)).collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn window() {
+        let mut q = CircularQueue::with_capacity(4);
+        q.extend(vec![1, 2, 3, 4, 5, 6]);
+
+        let w = q.windows(3).next().unwrap();
+        assert_eq!(w.as_slices(), (&[3, 4][..], &[5][..]));
+        assert_eq!(w.len(), 3);
+        assert_eq!(w[2], 5);
+        assert_eq!(w.get(3), None);
+        assert_eq!(w, &[3, 4, 5][..]);
+        assert_eq!(format!("{:?}", w), "[3, 4, 5]");
+
+        let w = q.windows(2).nth(2).unwrap();
+        assert_eq!(w.as_slices(), (&[5, 6][..], &[][..]));
+    }
+
+    #[test]
+    #[should_panic(expected = "window size must be non-zero")]
+    fn windows_zero() {
+        let q = CircularQueue::<i32>::with_capacity(3);
+        q.windows(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk size must be non-zero")]
+    fn chunks_zero() {
+        let q = CircularQueue::<i32>::with_capacity(3);
+        q.chunks(0);
+    }
+}
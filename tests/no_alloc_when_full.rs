@@ -0,0 +1,83 @@
+//! Checks that pushing into a full queue never allocates.
+
+#![cfg(all(has_global_allocator, has_alloc))]
+
+extern crate circular_queue;
+
+use circular_queue::{CircularQueue, Preallocation};
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn assert_no_allocations<F: FnOnce()>(f: F) {
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    f();
+    assert_eq!(ALLOCATIONS.load(Ordering::SeqCst), before);
+}
+
+// A single test, so that other tests running in parallel don't allocate.
+#[test]
+fn push_does_not_allocate_when_full() {
+    let mut eager = CircularQueue::with_capacity(16);
+    for i in 0..16 {
+        eager.push(i);
+    }
+    assert_no_allocations(|| {
+        for i in 0..1000 {
+            eager.push(i);
+        }
+    });
+
+    let mut lazy = CircularQueue::builder()
+        .capacity(100)
+        .preallocation(Preallocation::Lazy)
+        .build();
+    for i in 0..100 {
+        lazy.push(i);
+    }
+    assert_no_allocations(|| {
+        for i in 0..1000 {
+            lazy.push(i);
+        }
+    });
+
+    lazy.set_capacity(50);
+    assert_no_allocations(|| {
+        for i in 0..1000 {
+            lazy.push(i);
+        }
+    });
+
+    lazy.clear();
+    for i in 0..50 {
+        lazy.push(i);
+    }
+    assert_no_allocations(|| {
+        for i in 0..1000 {
+            lazy.push(i);
+        }
+    });
+}